@@ -39,27 +39,27 @@ pub enum Routes {
     Login {
         query: IndexMap<String, String>, // -> http://localhost:8000/login?name=JohnDoe
     },
-    #[guard = " => guard => forbidden"]
+    #[guard(check = guard, otherwise = forbidden)]
     Dashboard(pages::dashboard::Routes), // -> http://localhost:8000/dashboard/*
-    #[guard = "logged_user => admin_guard => forbidden_user"]
+    #[guard(model = logged_user, check = admin_guard, otherwise = forbidden_user)]
     Admin {
         // -> /admin/:id/*
         id: String,
         children: pages::admin::Route,
     },
     #[default_route]
-    #[view = " => not_found"] // -> http://localhost:8000/not_found*
+    #[view(render = not_found)] // -> http://localhost:8000/not_found*
     NotFound,
-    #[view = " => forbidden"] // -> http://localhost:8000/forbidden*
+    #[view(render = forbidden)] // -> http://localhost:8000/forbidden*
     Forbidden,
-    // #[view = " => nice_view"] // -> http://localhost:8000/forbidden*
+    // #[view(render = nice_view)] // -> http://localhost:8000/forbidden*
     // NiceView { id: String },
 
     // When putting together the view for this route, the "theme" in this
     // attribute sets the argument of the function signature to the "theme"
     // field like so: `home(&model.theme)`, where the `model` is the `Model`
     // struct for this module.
-    #[view = "theme => home"] // -> http://localhost:8000/
+    #[view(model = theme, render = home)] // -> http://localhost:8000/
     Home,
 }
 
@@ -138,7 +138,7 @@ pub enum Msg {
 fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
     match msg {
         Msg::UrlChanged(subs::UrlChanged(_url)) => {
-            router().current_route().init(model, orders);
+            router().init_current_route(model, orders);
         }
         Msg::Login(login_message) => pages::login::update(
             login_message,
@@ -156,6 +156,8 @@ fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
         }
         Msg::UserLogged(user) => {
             model.logged_user = Some(user);
+            // The guards read the user, a pending route can be allowed now.
+            router().init_current_route(model, orders);
         }
 
         Msg::SwitchToTheme(theme) => model.theme = theme,
@@ -166,7 +168,10 @@ fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
         Msg::GoForward => {
             router().request_moving_forward(|r| orders.notify(subs::UrlRequested::new(r)));
         }
-        Msg::Logout => model.logged_user = None,
+        Msg::Logout => {
+            model.logged_user = None;
+            router().init_current_route(model, orders);
+        }
         Msg::GoLogin => {
             // model.router.current_route = Some(Routes::Login {
             //     query: IndexMap::new(),
@@ -281,7 +286,7 @@ fn generate_root_nodes() -> Vec<Node<Msg>> {
                 "route",
                 IF!(    router().is_current_route(&route.0 ) => "active-route" )
             ],
-            attrs! { At::Href => &route.0.to_url() },
+            attrs! { At::Href => router().url_for(&route.0) },
             route.1,
         ]])
     }
@@ -339,7 +344,7 @@ fn generate_admin_nodes(model: &Model) -> Vec<Node<Msg>> {
                     IF!(admin_guard(model.logged_user.as_ref()).is_some() && !admin_guard(model.logged_user.as_ref()).unwrap()
                     => "locked-admin-route" )
             ],
-            attrs! { At::Href => &route.0.to_url() },
+            attrs! { At::Href => router().url_for(&route.0) },
             route.1,
         ]])
     }
@@ -383,7 +388,7 @@ fn generate_dashboard_nodes(model: &Model) -> Vec<Node<Msg>> {
                 IF!(   router().is_current_route(&route.0 ) => "active-route" )
                            IF!(guard(model).is_none() => "locked-route"   ),
             ],
-            attrs! { At::Href => &route.0.to_url() },
+            attrs! { At::Href => router().url_for(&route.0) },
             route.1,
         ]])
     }
@@ -28,13 +28,10 @@ pub fn init(
         not_found_model.id = id.to_string();
         not_found_model
     } else {
-        orders.notify(subs::UrlRequested::new(
-            Root::Admin {
-                id: id.to_string(),
-                children: Route::NotFound,
-            }
-            .to_url(),
-        ));
+        orders.notify(subs::UrlRequested::new(router().url_for(&Root::Admin {
+            id: id.to_string(),
+            children: Route::NotFound,
+        })));
         let mut not_found_model = Model::default();
         not_found_model.id = id.to_string();
         not_found_model
@@ -51,12 +48,12 @@ pub enum Msg {}
 
 #[derive(Debug, PartialEq, Clone, RoutingModules)]
 pub enum Route {
-    #[view = " => root"]
+    #[view(render = root)]
     Root,
-    #[view = " => manager"]
+    #[view(render = manager)]
     Manager,
     #[default_route]
-    #[view = " => not_found"]
+    #[view(render = not_found)]
     NotFound,
 }
 
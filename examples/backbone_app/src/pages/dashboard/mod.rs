@@ -15,7 +15,7 @@ pub enum Routes {
     },
     Statistics,
     #[default_route]
-    #[view = "=> root"]
+    #[view(render = root)]
     #[as_path = ""]
     Root,
 }
@@ -95,13 +95,12 @@ pub fn list(tasks: &[task::Model], list: &[u32]) -> Vec<Node<Msg>> {
 }
 
 pub fn render_task(task: &task::Model, is_checked: bool) -> Node<Msg> {
-    let task_url = Root::Dashboard(Parent::Tasks {
+    let task_url = crate::router().url_for(&Root::Dashboard(Parent::Tasks {
         children: Routes::Task {
             id: task.task_no.to_string(),
         },
         query: IndexMap::new(),
-    })
-    .to_url();
+    }));
 
     let task_no = task.task_no;
     li![div![
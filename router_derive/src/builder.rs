@@ -1,217 +1,363 @@
-use proc_macro2::TokenStream;
-use quote::quote;
-use syn::{Attribute, Error, Field, Lit, LitStr, Meta, MetaNameValue, Result};
+use proc_macro2::{Span, TokenStream};
+use proc_macro_error::abort;
+use quote::{format_ident, quote, quote_spanned};
+use std::collections::HashMap;
+use syn::{
+    parse::{Parse, ParseStream},
+    punctuated::{Iter, Punctuated},
+    spanned::Spanned,
+    Attribute, Error, Expr, Field, Ident, Lit, LitStr, Meta, MetaNameValue, Path, Result, Token,
+    Variant,
+};
 
-/// Rebuild the content of a variant depending of the fields present in the
-/// original enum
-pub fn build_variant_arguments(
-    structs_tuple: (Option<&Field>, Option<&Field>, Option<&Field>),
-) -> TokenStream {
-    // Todo this code is ugly, need to find something better
-    match structs_tuple {
-        (id, query, children) if id.is_some() && query.is_some() && children.is_some() => {
-            quote! { id,query,children}
-        }
+/// The fields of a struct variant that are filled from the url.
+pub struct VariantPayload<'a> {
+    /// Fields bound to a `:name` segment of the `#[as_path]` pattern, in the
+    /// order they are declared in the variant.
+    pub path_params: Vec<&'a Field>,
+    pub id: Option<&'a Field>,
+    pub query: Option<&'a Field>,
+    pub children: Option<&'a Field>,
+    pub fragment: Option<&'a Field>,
+}
 
-        (id, query, children) if id.is_some() && query.is_some() && children.is_none() => {
-            quote! { id, query}
-        }
-        (id, query, children) if id.is_none() && query.is_some() && children.is_some() => {
-            quote! { query , children}
-        }
-        (id, query, children) if id.is_some() && children.is_some() && query.is_none() => {
-            quote! { id, children }
-        }
-        (id, query, children) if id.is_some() && query.is_none() && children.is_none() => {
-            quote! { id }
-        }
-        (id, query, children) if query.is_some() && id.is_none() && children.is_none() => {
-            quote! { query}
-        }
-        (id, query, children) if query.is_none() && id.is_none() & children.is_some() => {
-            quote! { children }
+impl<'a> VariantPayload<'a> {
+    /// Sort the fields of a struct variant. Every field which is not `id`,
+    /// `query`, `children` or `fragment` is a path parameter.
+    pub fn new(fields: Iter<'a, Field>) -> Self {
+        let find = |name: &str| fields.clone().find(|f| f.ident.as_ref().unwrap() == name);
+        let path_params = fields
+            .clone()
+            .filter(|f| {
+                let ident = f.ident.as_ref().unwrap();
+                ident != "id" && ident != "query" && ident != "children" && ident != "fragment"
+            })
+            .collect();
+        VariantPayload {
+            path_params,
+            id: find("id"),
+            query: find("query"),
+            children: find("children"),
+            fragment: find("fragment"),
         }
+    }
 
-        (id, query, children) if query.is_none() && id.is_none() & children.is_none() => {
-            quote! {}
-        }
-        (_, _, _) => {
-            quote! {}
-        }
+    /// The names of the fields in the order they are given to `init`, path
+    /// parameters first.
+    pub fn field_idents(&self) -> Vec<&'a Ident> {
+        self.path_params
+            .iter()
+            .chain(self.id.iter())
+            .chain(self.query.iter())
+            .chain(self.children.iter())
+            .chain(self.fragment.iter())
+            .map(|f| f.ident.as_ref().unwrap())
+            .collect()
     }
 }
 
-/// Assign only the payload defined by the field in the enu,
+/// Rebuild the content of a variant depending of the fields present in the
+/// original enum
+pub fn build_variant_arguments(payload: &VariantPayload) -> TokenStream {
+    // Bindings are not spanned to the enum, like any generated code.
+    let idents = payload
+        .field_idents()
+        .into_iter()
+        .map(|ident| format_ident!("{}", ident, span = Span::call_site()));
+    quote! { #(#idents),* }
+}
+
+/// Assign only the payload defined by the field in the enum.
+/// The `id`, the path parameters, the query and the children are parsed into
+/// the type of their field, so the assignment has to be used in a closure
+/// returning `Result<_, ParseError>`. Nothing panics on a bad url.
+///
+/// `pattern_params` are the names of the `:name` segments of the path, in the
+/// order `extract_path_parameters` returns their values in `path_params`.
 pub fn unwrap_url_payload_matching_field(
-    structs_tuple: (Option<&Field>, Option<&Field>, Option<&Field>),
+    payload: &VariantPayload,
+    pattern_params: &[String],
+    wildcard: bool,
 ) -> TokenStream {
-    // Todo this code is ugly, need to find something better
-    match structs_tuple {
-        (id, query, children) if id.is_some() && query.is_some() && children.is_some() => {
-            let sub_enum = &children.clone().expect("Should have extract url item").ty;
-            quote! { id : id.expect("Should have extract url item"),query : query.expect("Should have extract url item"),children :  #sub_enum::parse_path(&children.expect("Should have unwrap children route")).expect("Should have parse children Route but failed")}
-        }
-
-        (id, query, _) if id.is_some() && query.is_some() => {
-            quote! { id : id.expect("Should have extract url item"),query : query.expect("Should have extract url item")}
-        }
-        (id, query, children) if id.is_none() && query.is_some() && children.is_some() => {
-            let sub_enum = &children.clone().expect("Should have extract url item").ty;
-            quote! { query : query.expect("Should have extract url item"),children :  #sub_enum::parse_path(&children.expect("Should unwrap children route")).expect("Should have parse children Route")}
-        }
-        (id, query, children) if id.is_some() && children.is_some() && query.is_none() => {
-            let sub_enum = &children.clone().expect("Should have extract url item").ty;
-            quote! { id : id.expect("Should have extract url item"),children : #sub_enum::parse_path(&children.expect("Should unwrap children route")).expect("Should have parse children Route")}
-        }
-        (id, query, children) if id.is_some() && query.is_none() && children.is_none() => {
-            quote! { id : id.expect("Should have extract url item")}
-        }
-        (id, query, children) if query.is_some() && id.is_none() && children.is_none() => {
-            quote! { query : query.expect("Should have extract url item")}
-        }
-        (id, query, children) if query.is_none() && id.is_none() & children.is_some() => {
-            let sub_enum = &children.clone().expect("Should have extract url item").ty;
-            quote! { children :#sub_enum::parse_path(&children.expect("Should have extract url item").clone()).expect("Should have parse children Route")}
-        }
-
-        (_, _, _) => {
-            quote! {}
+    let mut assignments = Vec::new();
+    for field in &payload.path_params {
+        let ident = field.ident.as_ref().unwrap();
+        let index = pattern_params
+            .iter()
+            .position(|param| ident == param)
+            .expect("Path parameters should have been checked against the pattern");
+        if wildcard && index + 1 == pattern_params.len() {
+            assignments.push(quote! { #ident : PathRest::parse_rest(&path_params[#index])? });
+        } else {
+            assignments.push(quote! { #ident : parse_id_parameter(&path_params[#index])? });
         }
     }
+    if payload.id.is_some() {
+        assignments.push(quote! { id : parse_id_parameter(&id.ok_or(ParseError::NoMatch)?)? });
+    }
+    if payload.query.is_some() {
+        assignments.push(quote! { query : extract_query(rest)? });
+    }
+    if let Some(children) = payload.children {
+        let sub_enum = &children.ty;
+        assignments.push(
+            quote! { children : #sub_enum::parse_children_path(&children.ok_or(ParseError::NoMatch)?)? },
+        );
+    }
+    if payload.fragment.is_some() {
+        assignments.push(quote! { fragment : extract_fragment(rest)? });
+    }
+    quote! { #(#assignments),* }
 }
-pub fn inject_variant_payload_in_function_call(
-    structs_tuple: (Option<&Field>, Option<&Field>, Option<&Field>),
-) -> String {
-    // Todo this code is ugly, need to find something better
-    match structs_tuple {
-        (id, query, children) if id.is_some() && query.is_some() && children.is_some() => {
-            "id,query,children".to_string()
-        }
-
-        (id, query, children) if id.is_some() && query.is_some() && children.is_none() => {
-            "id,query".to_string()
-        }
-        (id, query, children) if id.is_none() && query.is_some() && children.is_some() => {
-            "query,children".to_string()
-        }
-        (id, query, children) if id.is_some() && children.is_some() && query.is_none() => {
-            "id,children".to_string()
-        }
-        (id, query, children) if id.is_some() && query.is_none() && children.is_none() => {
-            "id".to_string()
-        }
-        (id, query, children) if query.is_some() && id.is_none() && children.is_none() => {
-            "query".to_string()
-        }
-        (id, query, children) if query.is_none() && id.is_none() & children.is_some() => {
-            "children".to_string()
-        }
 
-        (id, query, children) if query.is_none() && id.is_none() & children.is_none() => {
-            "".to_string()
-        }
-        (_, _, _) => "".to_string(),
-    }
+pub fn inject_variant_payload_in_function_call(payload: &VariantPayload) -> String {
+    payload
+        .field_idents()
+        .iter()
+        .map(std::string::ToString::to_string)
+        .collect::<Vec<String>>()
+        .join(",")
 }
 
 pub fn extract_query_field_to_string() -> TokenStream {
-    quote! { convert_to_string(&query)}
+    quote! { query_to_string(&query).unwrap_or_default() }
 }
-pub fn build_string_without_path_name(
-    structs_tuple: (Option<&Field>, Option<&Field>, Option<&Field>),
-) -> TokenStream {
-    // Todo this code is ugly, need to find something better
-    match structs_tuple {
-        (id, query, children) if id.is_some() && query.is_some() && children.is_some() => {
-            let query_string = extract_query_field_to_string();
-            quote! { format!("{}?{}",  id, children.clone().as_path() , #query_string)}
-        }
 
-        (id, query, children) if id.is_some() && query.is_some() && children.is_none() => {
-            let query_string = extract_query_field_to_string();
+/// Build the string of the url for a struct variant, `path` being the
+/// expression giving the path of the variant without the leading `/`.
+/// The id is percent-encoded so it stays a single segment.
+pub fn build_string_with_path_name(payload: &VariantPayload, path: TokenStream) -> TokenStream {
+    let mut format = "/{}".to_string();
+    let mut arguments = vec![path];
+    if payload.id.is_some() {
+        format += "/{}";
+        arguments.push(quote! { encode_path_segment(&id.to_string()) });
+    }
+    if payload.children.is_some() {
+        format += "{}";
+        arguments.push(quote! { children.clone().as_path() });
+    }
+    if payload.query.is_some() {
+        format += "?{}";
+        arguments.push(extract_query_field_to_string());
+    }
+    if payload.fragment.is_some() {
+        format += "{}";
+        arguments.push(quote! { fragment_to_string(fragment.as_deref()) });
+    }
+    quote! { format!(#format, #(#arguments),*) }
+}
 
-            quote! { format!("/{}?{}",  id, #query_string)}
-        }
-        (id, query, children) if id.is_none() && query.is_some() && children.is_some() => {
-            let query_string = extract_query_field_to_string();
+/// Get the content of an attribute
+/// For example it will get the string `home` from `#[as_path="home"]`
+pub fn get_string_from_attribute(attribute_name: &str, attr: &Attribute) -> Result<Option<LitStr>> {
+    if !attr.path.is_ident(attribute_name) {
+        return Ok(None); // not our attribute
+    }
+    match attr.parse_meta()? {
+        Meta::NameValue(MetaNameValue {
+            lit: Lit::Str(name),
+            ..
+        }) => Some(Some(name)),
+        _ => None,
+    }
+    .ok_or_else(|| Error::new_spanned(attr, format!("expected #[{} = \"...\"]", attribute_name)))
+}
 
-            quote! { format!("/{}?{}",   children.clone().as_path(),#query_string)}
-        }
-        (id, query, children) if id.is_some() && query.is_none() && children.is_some() => {
-            quote! { format!("/{}{}", id,  children.clone().as_path())}
-        }
-        (id, query, children) if id.is_some() && query.is_none() && children.is_none() => {
-            quote! { format!("/{}", id)}
-        }
-        (id, query, children) if id.is_none() && query.is_some() && children.is_none() => {
-            let query_string = extract_query_field_to_string();
-            quote! { format!("/?{}",#query_string)}
-        }
-        (id, query, children) if id.is_none() && query.is_none() && children.is_some() => {
-            quote! { format!("/{}",    children.clone().as_path())}
-        }
+/// A `key = value` argument of an attribute, such as `check = admin_guard`.
+struct AttributeArgument {
+    key: Ident,
+    value: Expr,
+}
 
-        (_, _, _) => {
-            quote! { format!("/")}
-        }
+impl Parse for AttributeArgument {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let key = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let value = input.parse()?;
+        Ok(AttributeArgument { key, value })
     }
 }
 
-pub fn build_string_with_path_name(
-    structs_tuple: (Option<&Field>, Option<&Field>, Option<&Field>),
-    name: Option<String>,
-) -> TokenStream {
-    // Todo this code is ugly, need to find something better
-    match structs_tuple {
-        (id, query, children) if id.is_some() && query.is_some() && children.is_some() => {
-            let query_string = extract_query_field_to_string();
-            quote! { format!("/{}/{}{}?{}", #name, id, children.clone().as_path() , #query_string)}
+/// The arguments of an attribute such as
+/// `#[guard(model = user, check = admin_guard, otherwise = forbidden)]`.
+///
+/// The deprecated string form `#[guard = "user => admin_guard => forbidden"]`
+/// gives the values of the keys in order, an empty or a missing value being
+/// left out.
+pub struct AttributeArguments {
+    attr: Attribute,
+    values: HashMap<String, Expr>,
+}
+
+impl AttributeArguments {
+    /// Parse the arguments of the attribute, each key being one of `keys` and
+    /// given once.
+    pub fn parse(attr: &Attribute, keys: &[&str]) -> Self {
+        let name = attr
+            .path
+            .get_ident()
+            .map(ToString::to_string)
+            .unwrap_or_default();
+        let values = match attr.parse_meta() {
+            Ok(Meta::NameValue(MetaNameValue {
+                lit: Lit::Str(lit), ..
+            })) => Self::parse_string(&name, &lit, keys),
+            _ => Self::parse_list(&name, attr, keys),
+        };
+        AttributeArguments {
+            attr: attr.clone(),
+            values,
         }
+    }
 
-        (id, query, children) if id.is_some() && query.is_some() && children.is_none() => {
-            let query_string = extract_query_field_to_string();
+    fn parse_list(name: &str, attr: &Attribute, keys: &[&str]) -> HashMap<String, Expr> {
+        let arguments = attr
+            .parse_args_with(Punctuated::<AttributeArgument, Token![,]>::parse_terminated)
+            .unwrap_or_else(|err| {
+                abort!(
+                    err.span(),
+                    "expected #[{}({})]: {}",
+                    name,
+                    keys.iter()
+                        .map(|key| format!("{} = ...", key))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    err
+                )
+            });
+        let mut values = HashMap::new();
+        for AttributeArgument { key, value } in arguments {
+            let key_name = key.to_string();
+            if !keys.contains(&key_name.as_str()) {
+                abort!(
+                    key,
+                    "unknown argument `{}` for #[{}], expected one of `{}`",
+                    key_name,
+                    name,
+                    keys.join("`, `")
+                );
+            }
+            if values.insert(key_name.clone(), value).is_some() {
+                abort!(key, "`{}` is given more than once", key_name);
+            }
+        }
+        values
+    }
 
-            quote! { format!("/{}/{}?{}", #name, id, #query_string)}
-        }
-        (id, query, children) if id.is_none() && query.is_some() && children.is_some() => {
-            let query_string = extract_query_field_to_string();
+    fn parse_string(name: &str, lit: &LitStr, keys: &[&str]) -> HashMap<String, Expr> {
+        let string = lit.value();
+        let parts: Vec<&str> = string.split("=>").map(str::trim).collect();
+        if parts.len() > keys.len() {
+            abort!(
+                lit,
+                "expected #[{} = \"{}\"]",
+                name,
+                keys.iter()
+                    .map(|key| key.to_uppercase())
+                    .collect::<Vec<_>>()
+                    .join(" => ")
+            );
+        }
+        keys.iter()
+            .zip(parts)
+            .filter(|(_, part)| !part.is_empty())
+            .map(|(key, part)| {
+                let value = LitStr::new(part, lit.span())
+                    .parse()
+                    .unwrap_or_else(|err| abort!(lit, "invalid `{}`: {}", key, err));
+                ((*key).to_string(), value)
+            })
+            .collect()
+    }
 
-            quote! { format!("/{}/{}?{}", #name,  children.clone().as_path(),#query_string)}
-        }
-        (id, query, children) if id.is_some() && query.is_none() && children.is_some() => {
-            quote! { format!("/{}/{}{}", #name, id,  children.clone().as_path())}
-        }
-        (id, query, children) if id.is_some() && query.is_none() && children.is_none() => {
-            quote! { format!("/{}/{}", #name, id)}
-        }
-        (id, query, children) if id.is_none() && query.is_some() && children.is_none() => {
-            let query_string = extract_query_field_to_string();
-            quote! { format!("/{}?{}", #name,#query_string)}
+    /// Take the path of the function given for `key`, such as `admin_guard` or
+    /// `pages::admin::guard`.
+    pub fn function(&mut self, key: &str) -> Path {
+        match self.values.remove(key) {
+            Some(Expr::Path(expr)) => expr.path,
+            Some(other) => abort!(
+                other,
+                "`{}` expects the path of a function, such as `{} = my_function`",
+                key,
+                key
+            ),
+            None => abort!(self.attr, "missing `{} = ...`", key),
         }
-        (id, query, children) if id.is_none() && query.is_none() && children.is_some() => {
-            quote! { format!("/{}/{}", #name,   children.clone().as_path())}
+    }
+
+    /// Take the expression given for `key`, such as `any(is_admin, is_owner)`.
+    pub fn expression(&mut self, key: &str) -> Expr {
+        self.values
+            .remove(key)
+            .unwrap_or_else(|| abort!(self.attr, "missing `{} = ...`", key))
+    }
+
+    /// Take the path of the function given for `key`, if any.
+    pub fn optional_function(&mut self, key: &str) -> Option<Path> {
+        if self.values.contains_key(key) {
+            Some(self.function(key))
+        } else {
+            None
         }
+    }
 
-        (_, _, _) => {
-            quote! { format!("/{}", #name)}
+    /// Take the field of the model given for `key`, such as `user` or
+    /// `user.profile`, if any.
+    pub fn field(&mut self, key: &str) -> Option<Expr> {
+        match self.values.remove(key) {
+            Some(value @ Expr::Path(_)) | Some(value @ Expr::Field(_)) => Some(value),
+            Some(other) => abort!(
+                other.span(),
+                "`{}` expects a field of the model, such as `{} = user`",
+                key,
+                key
+            ),
+            None => None,
         }
     }
 }
 
-/// Get the content of an attribute
-/// For example it will get the string `home` from `#[as_path="home"]` and the
-/// content as a list from `#[view="=>my_view"]` and `#[guard="user =>
-/// admin_guard => forbidden_view"]`
-pub fn get_string_from_attribute(attribute_name: &str, attr: &Attribute) -> Result<Option<LitStr>> {
-    if !attr.path.is_ident(attribute_name) {
-        return Ok(None); // not our attribute
-    }
-    match attr.parse_meta()? {
-        Meta::NameValue(MetaNameValue {
-            lit: Lit::Str(name),
-            ..
-        }) => Some(Some(name)),
-        _ => None,
-    }
-    .ok_or_else(|| Error::new_spanned(attr, &format!("expected #[{} = \"...\"]", attribute_name)))
+/// Build a warning for every variant attribute in the deprecated string form,
+/// such as `#[guard = "user => admin_guard"]`, given with the keys of its list
+/// form.
+///
+/// A derive cannot emit a warning on stable, so the warning comes from the use
+/// of a `#[deprecated]` constant, spanned to the string of the attribute.
+pub fn deprecated_string_attributes(
+    variants: Iter<'_, Variant>,
+    attributes: &[(&str, &[&str])],
+) -> TokenStream {
+    let warnings = variants
+        .flat_map(|variant| variant.attrs.iter())
+        .filter_map(|attr| {
+            let (name, keys) = attributes
+                .iter()
+                .find(|(name, _)| attr.path.is_ident(name))?;
+            let lit = match attr.parse_meta() {
+                Ok(Meta::NameValue(MetaNameValue {
+                    lit: Lit::Str(lit), ..
+                })) => lit,
+                _ => return None,
+            };
+            let note = format!(
+                "#[{} = \"...\"] is deprecated, use #[{}({})] instead",
+                name,
+                name,
+                keys.iter()
+                    .map(|key| format!("{} = ...", key))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            let use_constant = quote_spanned! { lit.span() => string_attribute };
+            Some(quote! {
+                const _: () = {
+                    #[deprecated(note = #note)]
+                    #[allow(non_upper_case_globals)]
+                    const string_attribute: () = ();
+                    #use_constant
+                };
+            })
+        });
+    quote! { #(#warnings)* }
 }
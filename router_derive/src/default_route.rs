@@ -1,34 +1,68 @@
 use proc_macro_error::{abort, Diagnostic, Level};
 
-use syn::{parse::Result, punctuated::Iter, Attribute, Ident, Variant};
+use syn::{parse::Result, punctuated::Iter, Attribute, Fields, Ident, Variant};
 
 /// Identify the default route and catch error if none or too many
 pub fn get_default_route(variants: Iter<'_, Variant>) -> Result<Variant> {
-    let mut i = 0;
-    let mut default_variant: Option<Variant> = None;
-    for v in variants {
-        let default = variant_default_route(v.ident.clone(), &v.attrs);
-        if default {
-            i += 1;
-            default_variant = Some(v.clone());
-        }
-    }
-    if i == 0 {
-        abort!(Diagnostic::new(
+    match find_default_route(variants) {
+        Some(default_variant) => Ok(default_variant),
+        None => abort!(Diagnostic::new(
             Level::Error,
             "You need at least one default route with the attribute #[default_route].".into()
-        ));
-    } else if i > 1 {
+        )),
+    }
+}
+
+/// Find the default route if any and catch error if too many
+pub fn find_default_route(variants: Iter<'_, Variant>) -> Option<Variant> {
+    let mut defaults = variants.filter(|v| variant_default_route(v.ident.clone(), &v.attrs));
+    let default_variant = defaults.next().cloned();
+    if defaults.next().is_some() {
         abort!(Diagnostic::new(
             Level::Error,
             "You cannot have multiple default routes.".into()
         ));
-    } else {
-        Ok(default_variant.unwrap())
     }
+    default_variant
 }
 
 /// Check if default_route exist
 fn variant_default_route(_: Ident, attrs: &[Attribute]) -> bool {
     attrs.iter().any(|attr| attr.path.is_ident("default_route"))
 }
+
+/// The unmatched url a default route can carry.
+pub enum DefaultRouteUrl {
+    /// `NotFound { path: String }`
+    Path,
+    /// `NotFound(Url)`
+    Url,
+}
+
+/// Get what the default route carries, `None` for a unit variant.
+pub fn default_route_url(variant: &Variant) -> Option<DefaultRouteUrl> {
+    match &variant.fields {
+        Fields::Unit => None,
+        Fields::Named(fields)
+            if fields.named.len() == 1
+                && fields
+                    .named
+                    .iter()
+                    .all(|f| f.ident.as_ref().unwrap() == "path") =>
+        {
+            Some(DefaultRouteUrl::Path)
+        }
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => Some(DefaultRouteUrl::Url),
+        _ => abort!(Diagnostic::spanned(
+            variant.ident.span(),
+            Level::Error,
+            "Default route need to be simple: a unit variant, `{ path: String }` or `(Url)`."
+                .into()
+        )),
+    }
+}
+
+/// Check if the variant is the default route.
+pub fn is_default_route(variant: &Variant) -> bool {
+    variant_default_route(variant.ident.clone(), &variant.attrs)
+}
@@ -1,90 +1,224 @@
-use proc_macro_error::{abort, Diagnostic, Level};
+use convert_case::{Case, Casing};
 
-use crate::builder::get_string_from_attribute;
+use crate::builder::AttributeArguments;
 use proc_macro2::TokenStream;
-use quote::quote;
-use syn::{Attribute, Ident};
-/// Extract the value from the [#guard  = "model_prop => guard_function"]
-pub fn variant_guard_path_tuple(
-    _ident: Ident,
-    attrs: std::slice::Iter<'_, Attribute>,
-) -> Option<(String, String, String)> {
-    let mut attrs = attrs.filter_map(|attr| match get_string_from_attribute("guard", attr) {
-        Ok(op) => op,
-        Err(err) => abort!(Diagnostic::new(Level::Error, err.to_string())),
-    });
-    let guard_scope = if attrs.clone().count() > 1 {
-        abort!(Diagnostic::new(
-            Level::Error,
-            "Multiple guard defined are not supported.".into()
-        ))
-    } else if let Some(name) = attrs.next() {
-        name.value()
-    } else {
-        "".to_string()
-    };
-    if guard_scope.is_empty() {
-        None
-    } else {
-        let string_to_parse = guard_scope;
-        let guard_scope_string: Vec<&str> = string_to_parse.split("=>").collect();
-        let mut guard_scope_string_iter = guard_scope_string.iter();
-        let guard_path = guard_scope_string_iter.next().unwrap_or_else(|| {
-            panic!(
-                "expect path for  #[guard_path = PATH => GUARD_FUNCTION => REDIRECT_VIEW] but got \
-                 this {:?}",
-                string_to_parse
-            )
-        });
-        let guard_function = guard_scope_string_iter.next().unwrap_or_else(|| {
-            panic!(
-                "expect path for  #[guard_path = PATH => GUARD_FUNCTION => REDIRECT_VIEW] but got \
-                 this {:?}",
-                string_to_parse
-            )
-        });
-        let guard_redirect = guard_scope_string_iter.next().unwrap_or_else(|| {
-            panic!(
-                "expect path for  #[guard_path = PATH => GUARD_FUNCTION => REDIRECT_VIEW] but got \
-                 this {:?}",
-                string_to_parse
-            )
-        });
-        Some((
-            guard_path.trim().to_string(),
-            guard_function.trim().to_string(),
-            guard_redirect.trim().to_string(),
-        ))
+use proc_macro_error::abort;
+use quote::{format_ident, quote};
+use syn::{punctuated::Iter, Attribute, Expr, Fields, Ident, Path, Variant};
+
+/// The guard of a variant from
+/// `#[guard(model = logged_user, check = admin_guard, otherwise = forbidden_user)]`.
+pub struct Guard {
+    /// The field of the model given to the functions, the whole model if
+    /// `None`.
+    pub model: Option<Expr>,
+    /// The functions checking the model, which return an `Option<bool>` with
+    /// `otherwise`, or the `router::Guard` returning a `GuardResult` without.
+    pub check: GuardCheck,
+    /// The view displayed when a check does not return `Some(true)`.
+    pub otherwise: Option<Path>,
+}
+
+/// The check of a guard, a function or checks combined with
+/// `all(logged_in, admin)` and `any(admin, owner)`.
+pub enum GuardCheck {
+    Function(Path),
+    /// Allowed if all the checks allow, checked in order, the first other
+    /// result wins.
+    All(Vec<GuardCheck>),
+    /// Allowed if one of the checks allows, checked in order, the result of
+    /// the first check wins if none does.
+    Any(Vec<GuardCheck>),
+}
+
+impl GuardCheck {
+    fn parse(expr: Expr) -> Self {
+        match expr {
+            Expr::Path(expr) => GuardCheck::Function(expr.path),
+            Expr::Call(call) => {
+                let combinator = match &*call.func {
+                    Expr::Path(func) if func.path.is_ident("all") => GuardCheck::All,
+                    Expr::Path(func) if func.path.is_ident("any") => GuardCheck::Any,
+                    func => abort!(func, "expected `all(...)` or `any(...)`"),
+                };
+                if call.args.is_empty() {
+                    abort!(call, "expected at least one guard to combine");
+                }
+                combinator(call.args.into_iter().map(GuardCheck::parse).collect())
+            }
+            other => abort!(
+                other,
+                "`check` expects the path of a function, or `all(...)` and `any(...)` of them"
+            ),
+        }
+    }
+}
+
+/// The keys of `#[guard(model = ..., check = ..., otherwise = ...)]`.
+pub const GUARD_KEYS: &[&str] = &["model", "check", "otherwise"];
+
+/// Extract the guards of a variant from its `#[guard(...)]` attributes, in
+/// order, or the deprecated `#[guard = "model_prop => guard_function =>
+/// redirect_view"]`.
+pub fn variant_guards(attrs: std::slice::Iter<'_, Attribute>) -> Vec<Guard> {
+    attrs
+        .filter(|attr| attr.path.is_ident("guard"))
+        .map(|attr| {
+            let mut arguments = AttributeArguments::parse(attr, GUARD_KEYS);
+            Guard {
+                model: arguments.field("model"),
+                check: GuardCheck::parse(arguments.expression("check")),
+                otherwise: arguments.optional_function("otherwise"),
+            }
+        })
+        .collect()
+}
+
+/// Build the arms of `router::Guarded::check_guard` for the guarded variants
+/// and the variants with nested routes.
+///
+/// The guards of a variant are checked in order and the first one which does
+/// not allow the route wins, the guards of its nested routes being checked
+/// last.
+/// With `otherwise`, a check allows the route if it returns `Some(true)` and
+/// denies it with the `otherwise` view if not.
+pub fn guard_snippets(variants: Iter<'_, Variant>) -> Vec<TokenStream> {
+    variants
+        .filter_map(|variant| {
+            let ident = &variant.ident;
+            let mut results: Vec<TokenStream> = variant_guards(variant.attrs.iter())
+                .iter()
+                .map(|guard| {
+                    let model_path = match &guard.model {
+                        Some(model) => quote! { scoped_state.#model.as_ref() },
+                        None => quote! { scoped_state },
+                    };
+                    check_result(&guard.check, &model_path, guard.otherwise.as_ref())
+                })
+                .collect();
+            let pattern = match nested_routes_result(variant) {
+                Some((pattern, result)) => {
+                    results.push(result);
+                    pattern
+                }
+                None => quote! { Self::#ident { .. } },
+            };
+            if results.is_empty() {
+                return None;
+            }
+            let result = all_results(results);
+            Some(quote! {
+                #pattern => #result
+            })
+        })
+        .collect()
+}
+
+/// Get the pattern binding the nested routes of a module variant as
+/// `children`, `Dashboard(children)` or `Admin { id, children }`, and the result of their
+/// guards checked with the model of the module, mapped to the variant.
+fn nested_routes_result(variant: &Variant) -> Option<(TokenStream, TokenStream)> {
+    let ident = &variant.ident;
+    if variant.attrs.iter().any(|attr| attr.path.is_ident("view")) {
+        return None;
     }
+    let (pattern, redirect) = match &variant.fields {
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => (
+            quote! { Self::#ident(children) },
+            quote! { Self::#ident(route) },
+        ),
+        Fields::Named(fields) => {
+            let names: Vec<&Ident> = fields
+                .named
+                .iter()
+                .filter_map(|field| field.ident.as_ref())
+                .collect();
+            if !names.iter().any(|name| *name == "children") {
+                return None;
+            }
+            let others = names.iter().filter(|name| **name != "children");
+            (
+                quote! { Self::#ident { #(#names),* } },
+                quote! { Self::#ident { children: route, #(#others: #others.clone()),* } },
+            )
+        }
+        _ => return None,
+    };
+    let module = format_ident!("{}", ident.to_string().to_case(Case::Snake));
+    let result = quote! {
+        match router::Guarded::check_guard(children, &scoped_state.#module) {
+            router::GuardResult::Allow => router::GuardResult::Allow,
+            router::GuardResult::Deny(view) => router::GuardResult::Deny(view.map_msg(Msg::#ident)),
+            router::GuardResult::Redirect(route) => router::GuardResult::Redirect(#redirect),
+            router::GuardResult::Pending => router::GuardResult::Pending,
+        }
+    };
+    Some((pattern, result))
 }
 
-/// Add a guard on the view if guard_scope contains value from [#guard  =
-/// "model_prop => guard_function"]
-pub fn add_guard_to_view(
-    view_to_load: TokenStream,
-    guard_scope: (String, String, String),
+/// Build the `GuardResult` of a check.
+fn check_result(
+    check: &GuardCheck,
+    model_path: &TokenStream,
+    otherwise: Option<&Path>,
 ) -> TokenStream {
-    let (model_scope, function_path, redirect) = guard_scope;
-    let model_path = if model_scope.is_empty() {
-        "scoped_state".to_string()
-    } else {
-        format!("scoped_state.{}.as_ref()", model_scope)
+    let results = |checks: &[GuardCheck]| {
+        checks
+            .iter()
+            .map(|check| check_result(check, model_path, otherwise))
+            .collect::<Vec<_>>()
     };
-    let redirect_token: TokenStream = format!(" {}({})", redirect, model_path).parse().unwrap();
-    let guard_function_token: TokenStream = format!("{}({})", function_path, model_path)
-        .parse()
-        .unwrap();
-    quote! {
-         if let Some(authenticated) = #guard_function_token {
-               if authenticated {
-                  #view_to_load
-                }
-                else {
-                  #redirect_token
+    match check {
+        GuardCheck::Function(check) => match otherwise {
+            Some(otherwise) => quote! {
+                if let Some(true) = #check(#model_path) {
+                    router::GuardResult::Allow
+                } else {
+                    router::GuardResult::Deny(#otherwise(#model_path))
                 }
-            } else {
-               #redirect_token
+            },
+            None => quote! { router::Guard::check(&#check, #model_path) },
+        },
+        GuardCheck::All(checks) => all_results(results(checks)),
+        GuardCheck::Any(checks) => any_results(results(checks)),
+    }
+}
+
+/// Check the results in order until one does not allow the route.
+fn all_results(results: Vec<TokenStream>) -> TokenStream {
+    let mut results = results.into_iter().rev();
+    let last = results
+        .next()
+        .unwrap_or_else(|| quote! { router::GuardResult::Allow });
+    results.fold(last, |next, result| {
+        quote! {
+            match #result {
+                router::GuardResult::Allow => #next,
+                result => result,
             }
+        }
+    })
+}
 
+/// Check the results in order until one allows the route, the first result
+/// being kept if none does.
+fn any_results(results: Vec<TokenStream>) -> TokenStream {
+    let mut results = results.into_iter();
+    let first = results
+        .next()
+        .unwrap_or_else(|| quote! { router::GuardResult::Allow });
+    let others = results.rev().fold(quote! { first }, |next, result| {
+        quote! {
+            match #result {
+                router::GuardResult::Allow => router::GuardResult::Allow,
+                _ => #next,
+            }
+        }
+    });
+    quote! {
+        match #first {
+            router::GuardResult::Allow => router::GuardResult::Allow,
+            first => #others,
+        }
     }
 }
@@ -1,8 +1,8 @@
 use convert_case::{Case, Casing};
 
 use crate::{
-    builder::{build_variant_arguments, inject_variant_payload_in_function_call},
-    view::variant_local_view_tuple,
+    builder::{build_variant_arguments, inject_variant_payload_in_function_call, VariantPayload},
+    view::{variant_local_view, LocalView},
 };
 use proc_macro2::TokenStream;
 use proc_macro_error::{abort, Diagnostic, Level};
@@ -14,14 +14,14 @@ pub fn module_init_snippets(
     modules_path: Option<String>,
 ) -> Vec<TokenStream> {
     let len = variants.len();
-    let snippets = variants.enumerate().map(|(_, variant)| {
+    let snippets = variants.map(|variant| {
         let Variant {
             attrs,
             ident,
             fields,
             ..
         } = variant;
-        let local_view = variant_local_view_tuple(ident.clone(), attrs.iter());
+        let local_view = variant_local_view(attrs.iter());
 
         match fields {
             Fields::Unit => init_for_unit_variant(ident.clone(), local_view, modules_path.clone()),
@@ -69,12 +69,12 @@ pub fn module_init_snippets(
 
 fn init_for_unit_variant(
     ident: Ident,
-    local_view: Option<(String, String)>,
+    local_view: Option<LocalView>,
     modules_path: Option<String>,
 ) -> TokenStream {
     // Do stuff about nested init maybe ?
     let init_to_load = match local_view {
-        Some((_, _)) => {
+        Some(_) => {
             quote! { {} }
         }
         None => get_init_token(ident.clone(), modules_path),
@@ -97,10 +97,7 @@ fn get_init_token(ident: Ident, modules_path: Option<String>) -> TokenStream {
         " previous_state.{} = {}::init(self.to_url(),
                     &mut previous_state.{},
                         &mut orders.proxy(Msg::{}),)  ",
-        module_name,
-        full_path,
-        module_name,
-        ident.to_string()
+        module_name, full_path, module_name, ident
     )
     .parse()
     .unwrap();
@@ -110,7 +107,7 @@ fn get_init_token(ident: Ident, modules_path: Option<String>) -> TokenStream {
 
 fn init_for_tuple_variant(
     ident: Ident,
-    local_view: Option<(String, String)>,
+    local_view: Option<LocalView>,
     fields: Iter<'_, Field>,
     modules_path: Option<String>,
 ) -> TokenStream {
@@ -123,7 +120,7 @@ fn init_for_tuple_variant(
 
     // Do stuff about nested init maybe ?
     let init_to_load = match local_view {
-        Some((_, _)) => {
+        Some(_) => {
             quote! { {} }
         }
         None => {
@@ -138,10 +135,7 @@ fn init_for_tuple_variant(
                     &mut previous_state.{},
                         nested,
                         &mut orders.proxy(Msg::{}),)  ",
-                module_name,
-                full_path,
-                module_name,
-                ident.to_string()
+                module_name, full_path, module_name, ident
             )
             .parse()
             .unwrap();
@@ -156,35 +150,20 @@ fn init_for_tuple_variant(
 
 fn init_for_init_struct_variant(
     ident: Ident,
-    local_view: Option<(String, String)>,
+    local_view: Option<LocalView>,
     fields: Iter<'_, Field>,
     modules_path: Option<String>,
 ) -> TokenStream {
-    let fields_to_extract = fields.clone();
-
-    let query_parameters = fields_to_extract
-        .clone()
-        .find(|f| f.ident.as_ref().unwrap() == "query");
-
-    let id_param = fields_to_extract
-        .clone()
-        .find(|f| f.ident.as_ref().unwrap() == "id");
-
-    let children = fields_to_extract
-        .clone()
-        .find(|f| f.ident.as_ref().unwrap() == "children");
-
-    let structs_tuple = (id_param, query_parameters, children);
-
-    let structs = build_variant_arguments(structs_tuple);
+    let payload = VariantPayload::new(fields);
+    let structs = build_variant_arguments(&payload);
     let module_name = ident.to_string().to_case(Case::Snake);
 
     // do stuff also for children init maybe
     //  let string_enum = build_string(structs_tuple, name.clone());
-    let payload: String = inject_variant_payload_in_function_call(structs_tuple);
+    let payload: String = inject_variant_payload_in_function_call(&payload);
 
     let init_to_load = match local_view {
-        Some((_, _)) => {
+        Some(_) => {
             quote! { {} }
         }
         None => {
@@ -201,7 +180,7 @@ fn init_for_init_struct_variant(
                     module_name,
                     full_path,
                     module_name, // = model name on parent model
-                    ident.to_string()
+                    ident
                 )
                 .parse()
                 .unwrap()
@@ -211,11 +190,7 @@ fn init_for_init_struct_variant(
                     &mut previous_state.{},
                     {},
                         &mut orders.proxy(Msg::{}),)  ",
-                    module_name,
-                    full_path,
-                    module_name,
-                    payload,
-                    ident.to_string()
+                    module_name, full_path, module_name, payload, ident
                 )
                 .parse()
                 .unwrap()
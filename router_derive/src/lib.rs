@@ -10,13 +10,21 @@ extern crate convert_case;
 extern crate proc_macro;
 extern crate proc_macro_error;
 
-use crate::{default_route::get_default_route, routing::routing_variant_snippets};
-use crate::{init::module_init_snippets, view::modules_view_snippets};
+use crate::{
+    builder::deprecated_string_attributes,
+    guard::{guard_snippets, GUARD_KEYS},
+    init::module_init_snippets,
+    view::{modules_view_snippets, VIEW_KEYS},
+};
+use crate::{
+    default_route::{default_route_url, find_default_route, get_default_route, DefaultRouteUrl},
+    routing::{fragment_snippets, routing_variant_snippets, scroll_restoration_snippets},
+};
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
 use proc_macro_error::{abort, proc_macro_error, Diagnostic, Level};
 use quote::quote;
-use syn::{parse_macro_input, Data, DeriveInput, Fields};
+use syn::{parse_macro_input, punctuated::Punctuated, Data, DeriveInput, Expr, Token};
 
 mod builder;
 mod default_route;
@@ -40,18 +48,41 @@ mod view;
 /// router().navigate_to_new(Route::Home);
 /// assert!(router().current_route(), Route::Home);
 /// ```
+///
+/// The routes are in the path of the url by default. Give a `RoutingMode` to
+/// have them in the fragment instead, such as `/#/dashboard/tasks`, and a base
+/// path when the app is served under it, such as `/apps/crm/dashboard`.
+///
+/// ```rust
+/// add_router!(RoutingMode::Hash);
+/// add_router!(RoutingMode::Path, "apps/crm");
+/// ```
 #[proc_macro]
-pub fn add_router(_item: TokenStream) -> TokenStream {
-    "thread_local! {
-    pub(crate) static ROUTER: Router<Routes> = Router::new();
-}
+#[proc_macro_error]
+pub fn add_router(item: TokenStream) -> TokenStream {
+    let arguments = parse_macro_input!(item with Punctuated::<Expr, Token![,]>::parse_terminated);
+    let mut arguments = arguments.iter();
+    let router = match (arguments.next(), arguments.next(), arguments.next()) {
+        (None, _, _) => quote! { Router::new() },
+        (Some(mode), None, _) => quote! { Router::with_routing_mode(#mode) },
+        (Some(mode), Some(base_path), None) => {
+            quote! { Router::with_routing_mode(#mode).with_base_path(#base_path) }
+        }
+        (Some(_), Some(_), Some(extra)) => abort!(
+            extra,
+            "add_router! takes a routing mode and a base path at most"
+        ),
+    };
+    TokenStream::from(quote! {
+        thread_local! {
+            pub(crate) static ROUTER: Router<Routes> = #router;
+        }
 
-/// Access the router from global state
-pub fn router() -> Router<Routes> {
-    ROUTER.with(Clone::clone,)
-}"
-    .parse()
-    .unwrap()
+        /// Access the router from global state
+        pub fn router() -> Router<Routes> {
+            ROUTER.with(Clone::clone,)
+        }
+    })
 }
 /// Synchronize the router to listen to subs::UrlRequested(requested_url, _)
 /// from seed and triggers navigation on callback to next route, or go back or
@@ -103,9 +134,98 @@ pub fn sync_router(_item: TokenStream) -> TokenStream {
 /// ```
 /// You can change the value of a path for a given route this way with
 /// `#[as_path = "my_new_name"]`.
+///
+/// A route matches whole segments and nothing but a query can be left once it
+/// matched, so `/administrator` does not match `Admin` and `/home/anything`
+/// does not match `Home`. A variant with `#[prefix_match]` matches any url
+/// starting with its path.
+///
+/// The most specific route is matched first whatever the declaration order, and
+/// two routes matching the same urls are a compile error.
+///
+/// The `id` field of a struct variant can be of any type implementing `FromStr`
+/// and `Display`, such as `String`, `u32` or `Uuid`. When the id in the url
+/// cannot be parsed into that type, the url does not match the variant and the
+/// router falls back to the default route.
+///
+/// The `query` field can be an `IndexMap<String, String>` or any type
+/// implementing `Serialize` and `Deserialize` into key value pairs. When the
+/// query cannot be deserialized, the url does not match the variant, and a
+/// query which cannot be serialized, such as a nested struct, is left out of
+/// the url.
+///
+/// A `fragment: Option<String>` field gets the fragment of the url after `#`.
+///
+/// A path can have several segments, and a segment starting with `:` binds to
+/// the field with the same name, parsed like `id`. A last `*` segment captures
+/// all the remaining segments into a `rest` field, which is a `Vec<String>` or
+/// a `String`.
+///
+/// ```rust
+/// #[derive(Debug, PartialEq, Clone, ParseUrl)]
+/// pub enum Routes {
+///     #[as_path = "orgs/:org_id/projects/:project_id"] // "/orgs/seed/projects/42"
+///     Project { org_id: String, project_id: u32 },
+///     #[as_path = "files/*"] // "/files/photos/beach.png"
+///     Files { rest: Vec<String> },
+/// }
+/// ```
+///
+/// The router restores the scroll position of a route when going back or
+/// forward to it, unless its variant has `#[no_scroll_restoration]`, such as a
+/// chat which always scrolls to its last message by itself.
+///
+/// The routes have no guard, `Guarded::check_guard` allows them for any
+/// model, so they can be nested in the routes of `RoutingModules`, whose
+/// guards check their nested routes. With `#[custom_guard]`, the routes
+/// implement `Guarded` themselves.
+///
+/// ```rust
+/// #[derive(Debug, PartialEq, Clone, ParseUrl)]
+/// #[custom_guard]
+/// pub enum Routes {
+///     Report,
+///     Archive,
+/// }
+///
+/// impl Guarded<Model, Msg> for Routes {
+///     fn check_guard(&self, model: &Model) -> GuardResult<Self, Msg> {
+///         match self {
+///             Self::Archive if !model.is_admin => GuardResult::Redirect(Self::Report),
+///             _ => GuardResult::Allow,
+///         }
+///     }
+/// }
+/// ```
 #[proc_macro_error]
-#[proc_macro_derive(ParseUrl, attributes(as_path))]
+#[proc_macro_derive(
+    ParseUrl,
+    attributes(as_path, prefix_match, no_scroll_restoration, custom_guard)
+)]
 pub fn derive_as_url(item: TokenStream) -> TokenStream {
+    let url_impl = TokenStream2::from(parse_url_impl(item.clone()));
+    let DeriveInput { ident, attrs, .. } = parse_macro_input!(item as DeriveInput);
+    let guarded_impl = if attrs.iter().any(|attr| attr.path.is_ident("custom_guard")) {
+        quote! {}
+    } else {
+        quote! {
+            impl<Model, Msg> router::Guarded<Model, Msg> for #ident {
+                fn check_guard(&self, _: &Model) -> router::GuardResult<Self, Msg> {
+                    router::GuardResult::Allow
+                }
+            }
+        }
+    };
+    TokenStream::from(quote! {
+        #url_impl
+
+        #guarded_impl
+    })
+}
+
+/// Implement `ParseUrl`, `AsPath`, `ParsePath` and `RouteBehavior` for the
+/// routes.
+fn parse_url_impl(item: TokenStream) -> TokenStream {
     let DeriveInput { ident, data, .. } = parse_macro_input!(item as DeriveInput);
     let variants = match data {
         Data::Enum(data) => data.variants,
@@ -116,23 +236,69 @@ pub fn derive_as_url(item: TokenStream) -> TokenStream {
     };
     let variants = variants.iter();
     let (as_snippets, parse_snippets) = routing_variant_snippets(variants.clone());
+    let scroll_snippets = scroll_restoration_snippets(variants.clone());
+    let fragment_snippets = fragment_snippets(variants.clone());
+
+    // A nested route which does not match goes to its own default route, and
+    // the default route can carry the url which did not match.
+    let (parse_children_path, default_route_for) = match find_default_route(variants) {
+        Some(default_variant) => {
+            let default_variant_ident = &default_variant.ident;
+            match default_route_url(&default_variant) {
+                None => (
+                    quote! {
+                        fn parse_children_path(path: &str) -> std::result::Result<Self, ParseError> {
+                            Self::parse_path(path).or(Ok(Self::#default_variant_ident))
+                        }
+                    },
+                    quote! {},
+                ),
+                Some(DefaultRouteUrl::Path) => (
+                    quote! {
+                        fn parse_children_path(path: &str) -> std::result::Result<Self, ParseError> {
+                            Self::parse_path(path).or_else(|_| Ok(Self::#default_variant_ident {
+                                path: format!("/{}", path.trim_start_matches('/')),
+                            }))
+                        }
+                    },
+                    quote! {
+                        fn default_route_for(url: &Url) -> Option<Self> {
+                            Some(Self::#default_variant_ident {
+                                path: url_to_path(url),
+                            })
+                        }
+                    },
+                ),
+                Some(DefaultRouteUrl::Url) => (
+                    quote! {},
+                    quote! {
+                        fn default_route_for(url: &Url) -> Option<Self> {
+                            Some(Self::#default_variant_ident(url.clone()))
+                        }
+                    },
+                ),
+            }
+        }
+        None => (quote! {}, quote! {}),
+    };
 
     let name = ident.to_string();
     TokenStream::from(quote! {
      impl router::ParseUrl for #ident {
         fn to_url(&self) -> Url {
-         let url : Url =    match self {
-                    #(#as_snippets),*
-                    }.parse().expect("Should have parsed but broke");
-                    url
+            path_to_url(&match self {
+                #(#as_snippets),*
+            })
+            .unwrap_or_default()
         }
 
         fn from_url(url: Url) -> std::result::Result<Self, ParseError>
          where
         Self: Sized + ParsePath {
-        let string_url = url.to_string();
-          Self::parse_path(&string_url)
+          Self::parse_path(&url_to_path(&url))
         }
+
+        #default_route_for
     }
             impl AsPath for #ident {
             fn as_path(self) -> String {
@@ -149,7 +315,26 @@ pub fn derive_as_url(item: TokenStream) -> TokenStream {
                         #parse_snippets
                         )
                     )*
-                    .map_err(|err| ParseError::By(#name.to_string(), Box::new(err)))
+                    .map_err(|err| ParseError::By(#name.to_string(), Box::new(err.with_unmatched_segment(next))))
+            }
+
+            #parse_children_path
+        }
+        impl router::RouteBehavior for #ident {
+            #[allow(unreachable_patterns)]
+            fn restores_scroll(&self) -> bool {
+                match self {
+                    #(#scroll_snippets,)*
+                    _ => true,
+                }
+            }
+
+            #[allow(unreachable_patterns)]
+            fn fragment(&self) -> Option<&str> {
+                match self {
+                    #(#fragment_snippets,)*
+                    _ => None,
+                }
             }
         }
     })
@@ -166,6 +351,10 @@ pub fn derive_as_url(item: TokenStream) -> TokenStream {
 ///     Root,
 /// }
 /// ```
+///
+/// The default route can carry the url which did not match as
+/// `NotFound { path: String }` or `NotFound(Url)`. It is empty by default and
+/// filled by the router with `ParseUrl::default_route_for`.
 #[proc_macro_error]
 #[proc_macro_derive(WithDefaultRoute, attributes(default_route))]
 pub fn derive_add_default_route(item: TokenStream) -> TokenStream {
@@ -188,32 +377,35 @@ pub fn derive_add_default_route(item: TokenStream) -> TokenStream {
     }
 
     let default_variant = default_route.unwrap();
+    let default_variant_ident = &default_variant.ident;
 
-    match default_variant.fields {
-        Fields::Named(_) => abort!(Diagnostic::new(
-            Level::Error,
-            "Default route need to be simple".into()
-        )),
-        Fields::Unnamed(_) => abort!(Diagnostic::new(
-            Level::Error,
-            "Default route need to be simple".into()
-        )),
-        Fields::Unit => {}
-    }
-
-    let default_variant_ident = default_variant.ident;
+    // A default route carrying the url is empty until the router fills it.
+    let (default_value, is_default) = match default_route_url(&default_variant) {
+        None => (
+            quote! { #ident::#default_variant_ident },
+            quote! { self == &#ident::#default_variant_ident },
+        ),
+        Some(DefaultRouteUrl::Path) => (
+            quote! { #ident::#default_variant_ident { path: String::new() } },
+            quote! { matches!(self, #ident::#default_variant_ident { .. }) },
+        ),
+        Some(DefaultRouteUrl::Url) => (
+            quote! { #ident::#default_variant_ident(Url::default()) },
+            quote! { matches!(self, #ident::#default_variant_ident(..)) },
+        ),
+    };
 
     TokenStream::from(quote! {
 
       impl Default for #ident {
             fn default() -> #ident {
-                #ident::#default_variant_ident
+                #default_value
             }
         }
 
       impl DefaultRoute<#ident> for #ident {
             fn is_default(&self) -> bool {
-                self == &#ident::#default_variant_ident
+                #is_default
             }
       }
     })
@@ -230,6 +422,33 @@ pub fn derive_add_default_route(item: TokenStream) -> TokenStream {
 /// The derive macro will call the init function , Model, Msg, Routes, Update,
 /// and View for the related module.
 ///
+///  - `#[view(model = theme, render = home)]` displays `home(&model.theme)`,
+/// and `#[view(render = home)]` gives the whole model.
+///  - `#[guard(model = logged_user, check = admin_guard, otherwise =
+/// forbidden_user)]` displays the view of the route if
+/// `admin_guard(model.logged_user.as_ref())` returns `Some(true)` and
+/// `forbidden_user` otherwise. `model` is optional here as well.
+///  - `#[guard(model = logged_user, check = login_required)]` without
+/// `otherwise` uses a `Guard`, such as a function returning a `GuardResult`.
+/// `GuardResult::Redirect` replaces the route in the `router()` of
+/// `add_router!` and displays the view of the route redirected to.
+///  - The guards of a route apply to its nested routes, `Dashboard(nested)` or
+/// `Admin { children, .. }`, whose own guards are checked after with the model
+/// of their module. A denied view is mapped to the `Msg` of the variant and a
+/// redirect to nested routes goes to the parent route holding them.
+///  - `#[guard]` can be repeated, the guards being checked in order and the
+/// first one which does not allow the route winning. `check = all(logged_in,
+/// admin)` allows the route if all the checks do and `check = any(admin,
+/// owner)` if one of them does, the result of the first check winning
+/// otherwise.
+///
+/// The guards are checked by `Guarded::check_guard` before both the view and
+/// the init of a route, so the init of a module is not called for a denied or
+/// pending route, and the init of the route redirected to is called instead.
+///
+/// The string forms `#[view = "theme => home"]` and `#[guard = "logged_user =>
+/// admin_guard => forbidden_user"]` still work but are deprecated.
+///
 ///
 ///
 /// ```rust
@@ -270,21 +489,21 @@ pub fn derive_add_default_route(item: TokenStream) -> TokenStream {
 ///     Login {
 ///         query: IndexMap<String, String,>, /// -> http:///localhost:8000/login?name=JohnDoe
 ///     },
-///     #[guard = " => guard => forbidden"]
+///     #[guard(check = guard, otherwise = forbidden)]
 ///     Dashboard(pages::dashboard::Routes,), /// -> http:///localhost:8000/dashboard/*
-///     #[guard = " => admin_guard => forbidden_user"]
+///     #[guard(check = admin_guard, otherwise = forbidden_user)]
 ///     Admin {
 ///         /// -> /admin/:id/*
 ///         id: String,
 ///         children: pages::admin::Routes,
 ///     },
 ///     #[default_route]
-///     #[view = " => not_found"] /// -> http:///localhost:8000/not_found*
+///     #[view(render = not_found)] /// -> http:///localhost:8000/not_found*
 ///     NotFound,
-///     #[view = " => forbidden"] /// -> http:///localhost:8000/forbidden*
+///     #[view(render = forbidden)] /// -> http:///localhost:8000/forbidden*
 ///     Forbidden,
 ///     #[as_path = ""]
-///     #[view = "theme => home"] /// -> http:///localhost:8000/
+///     #[view(model = theme, render = home)] /// -> http:///localhost:8000/
 ///     Home,
 /// }
 ///
@@ -354,10 +573,18 @@ pub fn derive_add_default_route(item: TokenStream) -> TokenStream {
 #[proc_macro_error]
 #[proc_macro_derive(
     RoutingModules,
-    attributes(as_path, view, guard, default_route, modules_path)
+    attributes(
+        as_path,
+        prefix_match,
+        no_scroll_restoration,
+        view,
+        guard,
+        default_route,
+        modules_path
+    )
 )]
 pub fn derive_add_module_load(item: TokenStream) -> TokenStream {
-    let add_url = derive_as_url(item.clone());
+    let add_url = parse_url_impl(item.clone());
     let default_route = derive_add_default_route(item.clone());
     let DeriveInput {
         ident, data, attrs, ..
@@ -379,15 +606,40 @@ pub fn derive_add_module_load(item: TokenStream) -> TokenStream {
     let modules_snippets = modules_view_snippets(variants.clone(), modules_path.clone());
 
     let init_snippets = module_init_snippets(variants.clone(), modules_path);
+
+    let deprecations = deprecated_string_attributes(
+        variants.clone(),
+        &[("guard", GUARD_KEYS), ("view", VIEW_KEYS)],
+    );
+
+    let guard_snippets = guard_snippets(variants);
     TokenStream::from(quote! {
     #url_impl
 
     #default_route_impl
 
+    #deprecations
+
+    impl router::Guarded<Model, Msg> for #ident {
+        #[allow(unused_variables, unreachable_patterns)]
+        fn check_guard(&self, scoped_state: &Model) -> router::GuardResult<Self, Msg> {
+            match self {
+                #(#guard_snippets,)*
+                _ => router::GuardResult::Allow,
+            }
+        }
+    }
+
     impl router::View<#ident, Model, Msg> for  #ident {
         fn view(&self, scoped_state: &Model) -> Node<Msg> {
-            match self {
-                 #(#modules_snippets),*
+            // A redirect is applied by `Router::init_current_route`, nothing
+            // is displayed until the router gets to the route redirected to.
+            match router::Guarded::check_guard(self, scoped_state) {
+                router::GuardResult::Allow => match self {
+                     #(#modules_snippets),*
+                },
+                router::GuardResult::Deny(view) => view,
+                router::GuardResult::Redirect(_) | router::GuardResult::Pending => Node::Empty,
             }
         }
     }
@@ -398,8 +650,12 @@ pub fn derive_add_module_load(item: TokenStream) -> TokenStream {
             previous_state: &mut Model,
             orders: &mut impl Orders<Msg>,
         ) {
-            match self {
-                #(#init_snippets),*
+            // `Router::init_current_route` follows the redirects of the
+            // guards before calling it.
+            if let router::GuardResult::Allow = router::Guarded::check_guard(self, previous_state) {
+                match self {
+                    #(#init_snippets),*
+                }
             }
         }
     }
@@ -1,16 +1,63 @@
 use convert_case::{Case, Casing};
 use proc_macro_error::{abort, Diagnostic, Level};
 
-use crate::builder::{
-    build_string_with_path_name, build_string_without_path_name, build_variant_arguments,
-    get_string_from_attribute, unwrap_url_payload_matching_field,
+use crate::{
+    builder::{
+        build_string_with_path_name, build_variant_arguments, get_string_from_attribute,
+        unwrap_url_payload_matching_field, VariantPayload,
+    },
+    default_route::{default_route_url, is_default_route, DefaultRouteUrl},
 };
-use quote::quote;
+use quote::{format_ident, quote};
 
-use proc_macro2::TokenStream;
-use std::collections::HashSet;
+use proc_macro2::{Span, TokenStream};
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+};
 use syn::{punctuated::Iter, Attribute, Field, Fields, Ident, Variant};
-use std::string::ParseError;
+
+/// How specific the path of a variant is. The most specific variants are parsed
+/// first, so the declaration order of the variants does not matter:
+/// - a path with more segments comes first, the empty path comes last.
+/// - a static segment comes before a path parameter or an `id`.
+/// - an exact match comes before a `#[prefix_match]`.
+/// - a `*` wildcard comes after any other path but the empty one.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct Specificity {
+    empty_path: bool,
+    wildcard: bool,
+    segments: Reverse<usize>,
+    parameters: Vec<bool>,
+    prefix_match: bool,
+    path_length: Reverse<usize>,
+}
+
+/// The segments a variant matches before its children, `None` being a path
+/// parameter or the `id`. Two variants with the same shape match the same urls.
+fn variant_shape(path_name: &str, fields: &Fields) -> Vec<Option<String>> {
+    let mut shape: Vec<Option<String>> = path_name
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| {
+            if segment.starts_with(':') {
+                None
+            } else {
+                Some(segment.to_string())
+            }
+        })
+        .collect();
+    if let Fields::Named(fields) = fields {
+        if fields
+            .named
+            .iter()
+            .any(|f| f.ident.as_ref().unwrap() == "id")
+        {
+            shape.push(None);
+        }
+    }
+    shape
+}
 
 /// Build the matching arms for the enum match for ParseUrl trait implementation
 pub fn routing_variant_snippets(
@@ -18,71 +65,140 @@ pub fn routing_variant_snippets(
 ) -> (Vec<TokenStream>, Vec<TokenStream>) {
     let len = variants.len();
 
-    let mut check_hash = HashSet::new();
-    let snippets = variants.enumerate().map(|(i, variant)| {
+    let mut check_shapes: HashMap<Vec<Option<String>>, Ident> = HashMap::new();
+    let snippets = variants.map(|variant| {
         let Variant {
             attrs,
             ident,
             fields,
             ..
         } = variant;
+        // The default route carrying the url is filled by `default_route_for`
+        // instead of being parsed, and goes back to the url it holds.
+        if is_default_route(variant) {
+            match default_route_url(variant) {
+                Some(DefaultRouteUrl::Path) => {
+                    return (
+                        quote! { Self::#ident { path } => format!("/{}", path.trim_start_matches('/')) },
+                        None,
+                    );
+                }
+                Some(DefaultRouteUrl::Url) => {
+                    return (
+                        quote! {
+                            Self::#ident(url) => url_to_path(&url)
+                        },
+                        None,
+                    );
+                }
+                None => {}
+            }
+        }
         let path_name = variant_path_segment(ident.clone(), attrs.iter());
-        let path_name_as_string = path_name.clone().unwrap_or_else(|| "".to_string());
-        if !check_hash.insert(path_name_as_string.clone()) {
-            abort!(Diagnostic::new(
+        let path_span = path_span(ident, attrs);
+        let prefix_match = variant_prefix_match(attrs.iter());
+        let path_name_as_string = path_name.clone().unwrap_or_default();
+        let shape = variant_shape(&path_name_as_string, fields);
+        if let Some(other) = check_shapes.insert(shape.clone(), ident.clone()) {
+            abort!(Diagnostic::spanned(
+                ident.span(),
                 Level::Error,
                 format!(
-                    "A Path must be unique and '{}' is found multiple times",
-                    path_name_as_string
+                    "The route {} is ambiguous with the route {}, they match the same urls.",
+                    ident, other
                 )
             ))
         }
+        let specificity = Specificity {
+            empty_path: path_name.is_none(),
+            wildcard: shape.last() == Some(&Some("*".to_string())),
+            segments: Reverse(shape.len()),
+            parameters: shape.iter().map(Option::is_none).collect(),
+            prefix_match,
+            path_length: Reverse(path_name_as_string.len()),
+        };
 
-        match fields {
-            Fields::Unit => {
-                // enforces variant with empty-string path is last variant
-                if path_name.is_none() && (i + 1) != len {
-                    abort!(Diagnostic::new(
-                        Level::Error,
-                        "Unit variant without a name must be declared last.".into()
-                    ))
-                }
+        let has_rest_field = match fields {
+            Fields::Named(fields) => fields
+                .named
+                .iter()
+                .any(|f| f.ident.as_ref().unwrap() == "rest"),
+            _ => false,
+        };
+        if path_name_as_string.split('/').any(|segment| segment == "*") && !has_rest_field {
+            abort!(Diagnostic::spanned(
+                ident.span(),
+                Level::Error,
+                format!(
+                    "The wildcard '*' of {} needs a struct variant with a `rest: PathRest` field.",
+                    ident
+                )
+            ))
+        }
 
-                unit_variant_snippets(ident.clone(), path_name)
-            }
+        if !matches!(fields, Fields::Named(_))
+            && !path_parameters(path_name.as_deref().unwrap_or_default()).is_empty()
+        {
+            abort!(
+                path_span,
+                "Path parameters in '{}' need a struct variant with matching fields.",
+                path_name_as_string
+            )
+        }
+
+        let (as_snippet, parse_snippet) = match fields {
+            Fields::Unit => unit_variant_snippets(ident.clone(), path_name, prefix_match),
             Fields::Unnamed(fields) => {
                 if path_name.is_none() {
-                    abort!(Diagnostic::new(
-                        Level::Error,
-                        "Empty path are not allowed in Routes with argument.".into()
-                    ))
+                    abort!(ident, "Empty path are not allowed in Routes with argument.")
                 }
-                tuple_variant_snippets(ident.clone(), path_name, fields.unnamed.iter())
+                tuple_variant_snippets(
+                    ident.clone(),
+                    path_name,
+                    prefix_match,
+                    fields.unnamed.iter(),
+                )
             }
             Fields::Named(fields) => {
                 if path_name.is_none() {
-                    abort!(Diagnostic::new(
-                        Level::Error,
-                        "Empty path are not allowed in Routes with arguments.".into()
-                    ))
+                    abort!(ident, "Empty path are not allowed in Routes with arguments.")
                 }
-                struct_variant_snippets(ident.clone(), path_name, fields.named.iter())
+                struct_variant_snippets(
+                    ident.clone(),
+                    path_name,
+                    path_span,
+                    prefix_match,
+                    fields.named.iter(),
+                )
             }
-        }
+        };
+        (as_snippet, Some((specificity, parse_snippet)))
     });
-    snippets.fold(
+    let (as_snippets, mut parse_snippets) = snippets.fold(
         (Vec::with_capacity(len), Vec::with_capacity(len)),
         |mut acc, x| {
             acc.0.push(x.0);
-            acc.1.push(x.1);
+            acc.1.extend(x.1);
             acc
         },
+    );
+    parse_snippets.sort_by(|a, b| a.0.cmp(&b.0));
+    (
+        as_snippets,
+        parse_snippets
+            .into_iter()
+            .map(|(_, parse_snippet)| parse_snippet)
+            .collect(),
     )
 }
-fn unit_variant_snippets(ident: Ident, path_name: Option<String>) -> (TokenStream, TokenStream) {
+fn unit_variant_snippets(
+    ident: Ident,
+    path_name: Option<String>,
+    prefix_match: bool,
+) -> (TokenStream, TokenStream) {
     (
         as_unit_variant(ident.clone(), path_name.clone()),
-        parse_unit_variant(ident, path_name),
+        parse_unit_variant(ident, path_name, prefix_match),
     )
 }
 
@@ -111,13 +227,19 @@ fn as_unit_variant(ident: Ident, path_name: Option<String>) -> TokenStream {
     }
 }
 
-fn parse_unit_variant(ident: Ident, path_name: Option<String>) -> TokenStream {
+/// The path has to match whole segments and nothing but a query can be left,
+/// unless the variant has `#[prefix_match]`.
+fn parse_unit_variant(ident: Ident, path_name: Option<String>, prefix_match: bool) -> TokenStream {
     let parser = match path_name {
-        Some(path_name) => quote! {
+        Some(path_name) if prefix_match => quote! {
             next.strip_prefix(#path_name).ok_or(err)
         },
+        Some(path_name) => quote! {
+            strip_path_segment(next, #path_name).ok_or(err)
+                .and_then(check_remaining_segments)
+        },
         None => quote! {
-        if next.is_empty() || next.strip_prefix("?").is_some()
+        if next.is_empty() || next.strip_prefix("?").is_some() || next.strip_prefix("#").is_some()
             { Ok(()) }
         else { Err(err) }
         },
@@ -129,22 +251,25 @@ fn parse_unit_variant(ident: Ident, path_name: Option<String>) -> TokenStream {
 fn tuple_variant_snippets(
     ident: Ident,
     name: Option<String>,
+    prefix_match: bool,
     fields: Iter<'_, Field>,
 ) -> (TokenStream, TokenStream) {
     (
         as_tuple_variant(ident.clone(), name.clone(), fields.clone()),
-        parse_tuple_variant(ident, name, fields),
+        parse_tuple_variant(ident, name, prefix_match, fields),
     )
 }
 
 fn struct_variant_snippets(
     ident: Ident,
     path_name: Option<String>,
+    path_span: Span,
+    prefix_match: bool,
     fields: Iter<'_, Field>,
 ) -> (TokenStream, TokenStream) {
     (
         as_struct_variant(ident.clone(), path_name.clone(), fields.clone()),
-        parse_struct_variant(ident, path_name, fields),
+        parse_struct_variant(ident, path_name, path_span, prefix_match, fields),
     )
 }
 fn as_tuple_variant(
@@ -153,10 +278,7 @@ fn as_tuple_variant(
     fields: Iter<'_, Field>,
 ) -> TokenStream {
     if fields.clone().count() != 1 {
-        abort!(Diagnostic::new(
-            Level::Error,
-            "Tuple variants may only have a single field.".into()
-        ))
+        abort!(ident, "Tuple variants may only have a single field.")
     }
     let format = match path_name {
         Some(path_name) => quote! { format!("/{}{}", #path_name, nested.clone().as_path()) },
@@ -172,29 +294,43 @@ fn as_struct_variant(
     path_name: Option<String>,
     fields: Iter<'_, Field>,
 ) -> TokenStream {
-    let fields_to_extract = fields.clone();
-
-    let query_parameters = fields_to_extract
-        .clone()
-        .find(|f| f.ident.as_ref().unwrap() == "query");
-
-    let id_param = fields_to_extract
-        .clone()
-        .find(|f| f.ident.as_ref().unwrap() == "id");
-
-    let children = fields_to_extract
-        .clone()
-        .find(|f| f.ident.as_ref().unwrap() == "children");
-
-    let structs_tuple = (id_param, query_parameters, children);
+    let payload = VariantPayload::new(fields);
+    let structs = build_variant_arguments(&payload);
 
-    let structs = build_variant_arguments(structs_tuple);
-
-    // let string_enum_with_no_name = build_string(structs_tuple );
-    let format = match &path_name {
-        Some(_) => build_string_with_path_name(structs_tuple, path_name.clone()),
-        None => build_string_without_path_name(structs_tuple),
+    let path_name = path_name.unwrap_or_default();
+    let params = path_parameters(&path_name);
+    let path = if params.is_empty() {
+        quote! { #path_name }
+    } else {
+        // "orgs/:org_id" -> format!("orgs/{}", org_id)
+        let format = path_name
+            .split('/')
+            .map(|segment| {
+                if segment.starts_with(':') || segment == "*" {
+                    "{}".to_string()
+                } else {
+                    segment.replace('{', "{{").replace('}', "}}")
+                }
+            })
+            .collect::<Vec<String>>()
+            .join("/");
+        let arguments = params.iter().map(|param| {
+            let param = format_ident!("{}", param);
+            quote! { encode_path_segment(&#param.to_string()) }
+        });
+        if path_name.split('/').next_back() == Some("*") {
+            // The last argument is the wildcard, "files/*" -> "files" when empty.
+            let arguments = arguments.take(params.len() - 1);
+            quote! {
+                format!(#format, #(#arguments,)* rest.as_rest())
+                    .trim_end_matches('/')
+                    .to_string()
+            }
+        } else {
+            quote! { format!(#format, #(#arguments),*) }
+        }
     };
+    let format = build_string_with_path_name(&payload, path);
     quote! {
         Self::#ident{#structs} => #format
     }
@@ -203,21 +339,25 @@ fn as_struct_variant(
 fn parse_tuple_variant(
     ident: Ident,
     path_name: Option<String>,
+    prefix_match: bool,
     fields: Iter<'_, Field>,
 ) -> TokenStream {
     if fields.clone().count() != 1 {
-        abort!(Diagnostic::new(
-            Level::Error,
-            "Tuple variants may only have a single field.".into()
-        ))
+        abort!(ident, "Tuple variants may only have a single field.")
     }
 
     // If path not empty, parse it otherwise go to the next part of the string.
+    // Only a nested route under a matched path falls back to its default route.
+    let strip_path = if prefix_match {
+        quote! { next.strip_prefix(#path_name) }
+    } else {
+        quote! { strip_path_segment(next, #path_name) }
+    };
     let parser = match path_name {
-        Some(path_name) => quote! {
-            next.strip_prefix(#path_name).ok_or(err)
+        Some(_) => quote! {
+            #strip_path.ok_or(err)
                 .and_then(|rest|
-                    ParsePath::parse_path(rest)
+                    ParsePath::parse_children_path(rest)
                 )
         },
         None => quote! {
@@ -231,61 +371,254 @@ fn parse_tuple_variant(
 fn parse_struct_variant(
     ident: Ident,
     path_name: Option<String>,
+    path_span: Span,
+    prefix_match: bool,
     fields: Iter<'_, Field>,
 ) -> TokenStream {
-    // let children = fields.find(|f| f.ident.as_ref().unwrap() == "children");
-    let id_param = fields.clone().find(|f| f.ident.as_ref().unwrap() == "id");
-    let query_parameters = fields
-        .clone()
-        .find(|f| f.ident.as_ref().unwrap() == "query");
+    let payload = VariantPayload::new(fields);
+    let path_name = path_name.unwrap_or_default();
+    let params = path_parameters(&path_name);
+    check_path_parameters(&ident, path_span, &params, &payload);
+    let wildcard = path_wildcard(&ident, path_span, &path_name, &payload);
+
+    let with_id_param = payload.id.is_some();
+    let with_children = payload.children.is_some();
+    let structs = unwrap_url_payload_matching_field(&payload, &params, wildcard);
+
+    // A path with segments is matched segment by segment so the values of the
+    // path parameters can be extracted, otherwise it is a simple prefix.
+    let path_parser = if path_name.contains('/') || !params.is_empty() {
+        quote! { extract_path_parameters(next, #path_name).ok_or(err) }
+    } else if prefix_match {
+        quote! { next.strip_prefix(#path_name).map(|rest| (Vec::new(), rest)).ok_or(err) }
+    } else {
+        quote! { strip_path_segment(next, #path_name).map(|rest| (Vec::new(), rest)).ok_or(err) }
+    };
+    // Payload not in the variant is ignored.
+    let payload_ident = |name: &str, with: bool| {
+        if with {
+            format_ident!("{}", name)
+        } else {
+            format_ident!("_")
+        }
+    };
+    let id = payload_ident("id", with_id_param);
+    // What is left after the id is the children path, or has to be empty.
+    let check_remaining = !with_children && !prefix_match;
+    let children = payload_ident("children", with_children || check_remaining);
+    let remaining_segments = if check_remaining {
+        quote! { check_remaining_segments(&children.unwrap_or_default())?; }
+    } else {
+        quote! {}
+    };
+    let path_params = if params.is_empty() {
+        format_ident!("_path_params")
+    } else {
+        format_ident!("path_params")
+    };
 
-    // Update when having children available.
-    let children = fields
-        .clone()
-        .find(|f| f.ident.as_ref().unwrap() == "children");
+    quote! {
+        #path_parser.and_then(|(#path_params, rest): (Vec<String>, &str)| {
+            // The query is deserialized into its own type by `extract_query`.
+            let (#id, _, #children) = extract_url_payload(rest.to_string(),#with_id_param,false,true)?;
+            #remaining_segments
+            Ok(Self::#ident{#structs})
+        })
+    }
+}
 
-    let structs_tuple = (id_param, query_parameters, children);
+/// Get the names of the `:name` segments of a path such as
+/// `orgs/:org_id/projects/:project_id`, a `*` wildcard binding to `rest`.
+fn path_parameters(path_name: &str) -> Vec<String> {
+    path_name
+        .split('/')
+        .filter_map(|segment| {
+            if segment == "*" {
+                Some("rest")
+            } else {
+                segment.strip_prefix(':')
+            }
+        })
+        .map(std::string::ToString::to_string)
+        .collect()
+}
 
-    let with_id_param = structs_tuple.0.is_some();
-    let with_query_params = structs_tuple.1.is_some();
-    let with_children = structs_tuple.2.is_some();
-    let structs = unwrap_url_payload_matching_field(structs_tuple);
+/// Check if the path ends with a `*` wildcard capturing the remaining
+/// segments, which cannot be followed by an `id` or `children`.
+fn path_wildcard(
+    ident: &Ident,
+    path_span: Span,
+    path_name: &str,
+    payload: &VariantPayload,
+) -> bool {
+    let mut segments = path_name.split('/');
+    if segments.next_back() != Some("*") {
+        if segments.any(|segment| segment == "*") {
+            abort!(
+                path_span,
+                "The wildcard '*' of {} must be the last segment.",
+                ident
+            )
+        }
+        return false;
+    }
+    if payload.id.is_some() || payload.children.is_some() {
+        abort!(
+            ident,
+            "{} captures the remaining segments with '*' and cannot have an id or children.",
+            ident
+        )
+    }
+    true
+}
 
-    // If path not empty, parse it and extract payload otherwise go to the next part
-    // of the string. Warning empty path does not support url payload extracting
-    // because url functions do not know the path is empty.
-    let parser = match path_name {
-        Some(path_name) => {
-            quote! {      next.strip_prefix(#path_name).ok_or(err)
-                     .map(|rest| extract_url_payload(rest.to_string(),#with_id_param,#with_query_params,#with_children ))
+/// Make sure every path parameter has a field with the same name and every
+/// field which is not `id`, `query` or `children` is a path parameter.
+///
+/// The errors about the path point at its `#[as_path]` and the ones about a
+/// field at the field.
+fn check_path_parameters(
+    ident: &Ident,
+    path_span: Span,
+    params: &[String],
+    payload: &VariantPayload,
+) {
+    let mut unique = HashSet::new();
+    for param in params {
+        if param.is_empty() || !unique.insert(param) {
+            abort!(
+                path_span,
+                "Path parameters of {} must have a unique name, found ':{}'.",
+                ident,
+                param
+            )
+        }
+        if !payload
+            .path_params
+            .iter()
+            .any(|f| f.ident.as_ref().unwrap() == param)
+        {
+            abort!(
+                path_span,
+                "Path parameter ':{}' has no matching field in {}.",
+                param,
+                ident
+            )
+        }
+    }
+    for field in &payload.path_params {
+        let field_ident = field.ident.as_ref().unwrap();
+        if !params.contains(&field_ident.to_string()) {
+            abort!(
+                field_ident,
+                "Field '{}' of {} is not a path parameter. Add ':{}' to the path or use id, \
+                 query or children.",
+                field_ident,
+                ident,
+                field_ident
+            )
+        }
+    }
+}
+
+/// Build the matching arms of `RouteBehavior::restores_scroll`, `false` for the
+/// variants with `#[no_scroll_restoration]` and the answer of the children
+/// for nested routes.
+pub fn scroll_restoration_snippets(variants: Iter<'_, Variant>) -> Vec<TokenStream> {
+    variants
+        .filter_map(|variant| {
+            let ident = &variant.ident;
+            if variant
+                .attrs
+                .iter()
+                .any(|attr| attr.path.is_ident("no_scroll_restoration"))
+            {
+                Some(quote! { Self::#ident { .. } => false })
+            } else {
+                nested_snippet(variant, &quote! { router::RouteBehavior::restores_scroll })
+            }
+        })
+        .collect()
+}
+
+/// Build the matching arms of `RouteBehavior::fragment`, the `fragment` field or
+/// the fragment of the children for nested routes.
+pub fn fragment_snippets(variants: Iter<'_, Variant>) -> Vec<TokenStream> {
+    variants
+        .filter_map(|variant| {
+            let ident = &variant.ident;
+            if has_named_field(variant, "fragment") {
+                Some(quote! { Self::#ident { fragment, .. } => fragment.as_deref() })
+            } else {
+                nested_snippet(variant, &quote! { router::RouteBehavior::fragment })
             }
+        })
+        .collect()
+}
+
+/// Build the matching arm calling `method` on the children of a nested route,
+/// if the variant has children. The payload of a tuple variant which is not a
+/// nested route, such as a `u32`, gets the provided `RouteBehavior` of the
+/// types parsed with `FromStr`.
+fn nested_snippet(variant: &Variant, method: &TokenStream) -> Option<TokenStream> {
+    let ident = &variant.ident;
+    match &variant.fields {
+        Fields::Unnamed(_) if !is_default_route(variant) => {
+            Some(quote! { Self::#ident(nested) => #method(nested) })
         }
-        None => quote! {
-             next.strip_prefix("").ok_or(err)
-            .map(|rest| extract_url_payload(rest.to_string(),#with_id_param,#with_query_params,#with_children ))
-        },
-    };
+        Fields::Named(_) if has_named_field(variant, "children") => {
+            Some(quote! { Self::#ident { children, .. } => #method(children) })
+        }
+        _ => None,
+    }
+}
 
-    quote! {
-        #parser.map(|(id, query, children)| Self::#ident{#structs})
+/// Check the variant has a named field with this name.
+fn has_named_field(variant: &Variant, name: &str) -> bool {
+    match &variant.fields {
+        Fields::Named(fields) => fields
+            .named
+            .iter()
+            .any(|field| field.ident.as_ref().is_some_and(|ident| ident == name)),
+        _ => false,
     }
 }
+
+/// Check if the variant keeps matching urls by prefix with `#[prefix_match]`.
+fn variant_prefix_match(mut attrs: std::slice::Iter<'_, Attribute>) -> bool {
+    attrs.any(|attr| attr.path.is_ident("prefix_match"))
+}
+
+/// Get the span of the `#[as_path = "..."]` literal of a variant, or of its
+/// name without one, for the errors about its path.
+fn path_span(ident: &Ident, attrs: &[Attribute]) -> Span {
+    attrs
+        .iter()
+        .find_map(|attr| get_string_from_attribute("as_path", attr).ok().flatten())
+        .map_or_else(|| ident.span(), |lit| lit.span())
+}
+
 fn variant_path_segment(ident: Ident, attrs: std::slice::Iter<'_, Attribute>) -> Option<String> {
     // get just "as_path" attributes of an identity
     let mut attrs = attrs.filter_map(|attr| match get_string_from_attribute("as_path", attr) {
         Ok(op) => op,
-        Err(err) => abort!(Diagnostic::new(Level::Error, err.to_string())),
+        Err(err) => abort!(err.span(), "{}", err),
     });
 
-    // make sure only one path specified for each variant
-    let path_name = if attrs.clone().count() > 1 {
-        abort!(Diagnostic::new(
-            Level::Error,
-            "Multiple path names defined.".into()
-        ))
     // we have an as_path = "...", get the "..."
-    } else if let Some(path_name) = attrs.next() {
-        path_name.value()
+    let path_name = if let Some(lit) = attrs.next() {
+        // make sure only one path specified for each variant
+        if let Some(other) = attrs.next() {
+            abort!(other, "Multiple path names defined.")
+        }
+        let path_name = lit.value();
+        if !path_name.is_empty() && path_name.split('/').any(str::is_empty) {
+            abort!(
+                lit,
+                "A renamed path cannot start or end with '/' or contain an empty segment."
+            )
+        }
+        path_name
     } else {
         // no path specified, so use default (snake case version of ident)
         ident.to_string().to_case(Case::Snake)
@@ -293,11 +626,6 @@ fn variant_path_segment(ident: Ident, attrs: std::slice::Iter<'_, Attribute>) ->
 
     if path_name.is_empty() {
         None
-    } else if path_name.contains('/') {
-        abort!(Diagnostic::new(
-            Level::Error,
-            "A renamed path cannot contain the character '/' ".into()
-        ))
     } else {
         Some(path_name)
     }
@@ -0,0 +1,31 @@
+use super::AsPath;
+use std::str::FromStr;
+
+/// How the router behaves with a route, implemented by `ParseUrl` for the
+/// routes.
+///
+/// The provided implementations restore the scroll position and have no
+/// fragment. A type used as the payload of a tuple variant, such as the `u32`
+/// of `Profile(u32)` or a `Uuid`, gets them as any type parsed with `FromStr`,
+/// as for `ParsePath`.
+#[allow(clippy::module_name_repetitions)]
+pub trait RouteBehavior {
+    /// Check the scroll position is restored when going back or forward to
+    /// the route, and reset when navigating to it.
+    ///
+    /// Enums deriving `ParseUrl` answer `false` for the variants with
+    /// `#[no_scroll_restoration]` and ask the children of nested routes.
+    fn restores_scroll(&self) -> bool {
+        true
+    }
+
+    /// Get the fragment of the url of the route.
+    ///
+    /// Enums deriving `ParseUrl` get the `fragment` field of the variant or
+    /// ask the children of nested routes.
+    fn fragment(&self) -> Option<&str> {
+        None
+    }
+}
+
+impl<T: FromStr + ToString + AsPath> RouteBehavior for T {}
@@ -0,0 +1,120 @@
+use super::{HistoryBackend, Scroll};
+use seed::{
+    prelude::{js_sys, web_sys, Closure, JsCast, JsValue},
+    Url,
+};
+
+/// The key of the router history index in the state of a browser history
+/// entry.
+const HISTORY_INDEX_KEY: &str = "seed_routing_history_index";
+
+fn history() -> Option<web_sys::History> {
+    web_sys::window()?.history().ok()
+}
+
+/// Get the state of the current browser history entry.
+fn state() -> JsValue {
+    history()
+        .and_then(|history| history.state().ok())
+        .unwrap_or(JsValue::NULL)
+}
+
+/// Call `callback` after the next render of Seed, which happens on the next
+/// animation frame.
+fn after_next_render(callback: impl FnOnce() + 'static) {
+    let request_frame = |callback: JsValue| {
+        if let Some(window) = web_sys::window() {
+            let _ = window.request_animation_frame(callback.unchecked_ref());
+        }
+    };
+    request_frame(Closure::once_into_js(move || {
+        request_frame(Closure::once_into_js(callback));
+    }));
+}
+
+/// The history of the web browser, with the History API.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BrowserHistory;
+
+impl HistoryBackend for BrowserHistory {
+    fn push(&self, url: &Url) {
+        if let Some(history) = history() {
+            let _ = history.push_state_with_url(&JsValue::NULL, "", Some(&url.to_string()));
+        }
+    }
+
+    fn replace(&self, url: &Url) {
+        if let Some(history) = history() {
+            let _ = history.replace_state_with_url(&state(), "", Some(&url.to_string()));
+        }
+    }
+
+    fn go(&self, delta: i32) {
+        if let Some(history) = history() {
+            let _ = history.go_with_delta(delta);
+        }
+    }
+
+    /// Read the router history index stored in the state of the current
+    /// browser history entry. Entries pushed by Seed or by another script have
+    /// no index.
+    fn history_index(&self) -> Option<usize> {
+        let state = state();
+        if !state.is_object() {
+            return None;
+        }
+        let index = js_sys::Reflect::get(&state, &JsValue::from_str(HISTORY_INDEX_KEY)).ok()?;
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        index.as_f64().map(|index| index as usize)
+    }
+
+    /// Store the router history index in the state of the current browser
+    /// history entry, so going back or forward with the browser buttons can be
+    /// matched with the router history. The index is merged into the state
+    /// already there, if it is an object.
+    fn set_history_index(&self, index: usize) {
+        if let Some(history) = history() {
+            let state = match state() {
+                state if state.is_object() => state,
+                _ => js_sys::Object::new().into(),
+            };
+            #[allow(clippy::cast_precision_loss)]
+            let stored = js_sys::Reflect::set(
+                &state,
+                &JsValue::from_str(HISTORY_INDEX_KEY),
+                &JsValue::from_f64(index as f64),
+            );
+            if stored.is_ok() {
+                let _ = history.replace_state(&state, "");
+            }
+        }
+    }
+
+    fn scroll_position(&self) -> (f64, f64) {
+        web_sys::window().map_or((0., 0.), |window| {
+            (
+                window.scroll_x().unwrap_or_default(),
+                window.scroll_y().unwrap_or_default(),
+            )
+        })
+    }
+
+    fn scroll_to(&self, scroll: &Scroll) {
+        let scroll = scroll.clone();
+        after_next_render(move || {
+            if let Some(window) = web_sys::window() {
+                match scroll {
+                    Scroll::Position(x, y) => window.scroll_to_with_x_and_y(x, y),
+                    Scroll::Fragment(fragment) => {
+                        if let Some(element) = window
+                            .document()
+                            .and_then(|document| document.get_element_by_id(&fragment))
+                        {
+                            element.scroll_into_view();
+                        }
+                    }
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,81 @@
+use super::log_navigation;
+use seed::prelude::Node;
+use std::fmt::Debug;
+
+/// The most redirects of the guards followed from a route, see
+/// `resolve_guards`.
+pub const MAX_REDIRECTS: usize = 8;
+
+/// What a guard decides for the route it protects.
+#[allow(clippy::large_enum_variant)]
+pub enum GuardResult<Routes, Msg> {
+    /// Display the view of the route.
+    Allow,
+    /// Display this view instead, the url stays on the route.
+    Deny(Node<Msg>),
+    /// Replace the route with this one in the router and the url, as
+    /// `Router::navigate_replace` does, and display its view.
+    Redirect(Routes),
+    /// The guard cannot decide yet, for example while the user is loaded, so
+    /// nothing is displayed until the model changes.
+    Pending,
+}
+
+/// A guard protecting a route with `#[guard(model = user, check = MyGuard)]`.
+///
+/// `Model` is what the guard gets from the model, the field given with
+/// `model`, as an `Option` of reference, or the whole model. The functions
+/// taking it and returning a `GuardResult` are guards as well.
+pub trait Guard<Model, Routes, Msg> {
+    /// Decide if the route is displayed.
+    fn check(&self, model: Model) -> GuardResult<Routes, Msg>;
+}
+
+impl<Model, Routes, Msg, F> Guard<Model, Routes, Msg> for F
+where
+    F: Fn(Model) -> GuardResult<Routes, Msg>,
+{
+    fn check(&self, model: Model) -> GuardResult<Routes, Msg> {
+        self(model)
+    }
+}
+
+/// The guards of the routes, implemented by `#[derive(RoutingModules)]` from
+/// their `#[guard(...)]` attributes.
+pub trait Guarded<Model, Msg>: Sized {
+    /// Check the guard of the route with the model, `GuardResult::Allow` for
+    /// a route without guard.
+    fn check_guard(&self, scoped_state: &Model) -> GuardResult<Self, Msg>;
+}
+
+/// Check the guards of the route with the model and follow their redirects.
+///
+/// Return the route reached and its result, which is never a
+/// `GuardResult::Redirect`. A redirect coming back to a route already checked,
+/// or more than `MAX_REDIRECTS` redirects, is an error of the guards and ends
+/// on `GuardResult::Pending`, so nothing is displayed.
+pub fn resolve_guards<Routes, Model, Msg>(
+    route: Routes,
+    model: &Model,
+) -> (Routes, GuardResult<Routes, Msg>)
+where
+    Routes: Debug + PartialEq + Guarded<Model, Msg>,
+{
+    let mut route = route;
+    let mut visited = Vec::new();
+    loop {
+        let redirect = match route.check_guard(model) {
+            GuardResult::Redirect(redirect) => redirect,
+            result => return (route, result),
+        };
+        visited.push(route);
+        if visited.contains(&redirect) || visited.len() > MAX_REDIRECTS {
+            log_navigation(&format!(
+                "the guards redirect in a loop or too many times: {:?} -> {:?}",
+                visited, redirect
+            ));
+            return (redirect, GuardResult::Pending);
+        }
+        route = redirect;
+    }
+}
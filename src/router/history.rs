@@ -0,0 +1,175 @@
+use seed::Url;
+use std::{cell::RefCell, convert::TryFrom, rc::Rc};
+
+/// The history of the browser as seen by the router.
+///
+/// The router stores the index of each route of its own history in the state
+/// of the entries, so it can follow the browser going back or forward.
+/// `BrowserHistory` is used by default and `MemoryHistory` runs without a
+/// browser, for native tests.
+pub trait HistoryBackend {
+    /// Add a new entry with the url after the current one.
+    fn push(&self, url: &Url);
+    /// Change the url of the current entry, keeping its state.
+    fn replace(&self, url: &Url);
+    /// Move back, with a negative `delta`, or forward in the entries.
+    fn go(&self, delta: i32);
+    /// Read the router history index stored in the state of the current entry.
+    /// Entries pushed by Seed or by another script have no index.
+    fn history_index(&self) -> Option<usize>;
+    /// Store the router history index in the state of the current entry.
+    fn set_history_index(&self, index: usize);
+    /// Get the horizontal and vertical scroll position of the page.
+    fn scroll_position(&self) -> (f64, f64);
+    /// Scroll the page once the route the router goes to is rendered.
+    fn scroll_to(&self, scroll: &Scroll);
+}
+
+/// Where the page is scrolled to when the router goes to a route.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Scroll {
+    /// The horizontal and vertical scroll position, `(0., 0.)` being the top.
+    Position(f64, f64),
+    /// The element with the fragment of the url as id.
+    Fragment(String),
+}
+
+/// A call made by the router to a `MemoryHistory`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum HistoryCall {
+    Push(Url),
+    Replace(Url),
+    Go(i32),
+    Scroll(Scroll),
+}
+
+#[derive(Debug)]
+struct MemoryHistoryData {
+    /// The url and the router history index of each entry.
+    entries: Vec<(Url, Option<usize>)>,
+    current: usize,
+    calls: Vec<HistoryCall>,
+    scroll_position: (f64, f64),
+}
+
+/// A history kept in memory which records the calls made to it, to test
+/// navigation without a browser. Clones share the same history.
+#[derive(Clone, Debug)]
+pub struct MemoryHistory {
+    data: Rc<RefCell<MemoryHistoryData>>,
+}
+
+impl Default for MemoryHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemoryHistory {
+    /// Create a history with a single entry with an empty url, as a new tab.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_url(Url::new())
+    }
+
+    /// Create a history with a single entry with the url, as a tab opened on
+    /// it.
+    #[must_use]
+    pub fn with_url(url: Url) -> Self {
+        Self {
+            data: Rc::new(RefCell::new(MemoryHistoryData {
+                entries: vec![(url, None)],
+                current: 0,
+                calls: Vec::new(),
+                scroll_position: (0., 0.),
+            })),
+        }
+    }
+
+    /// Get the url of the current entry.
+    #[must_use]
+    pub fn current_url(&self) -> Url {
+        let data = self.data.borrow();
+        data.entries[data.current].0.clone()
+    }
+
+    /// Get the number of entries.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.data.borrow().entries.len()
+    }
+
+    /// Check there is no entry, which never happens since a new history has
+    /// one.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.data.borrow().entries.is_empty()
+    }
+
+    /// Scroll the page, as the user would.
+    pub fn set_scroll_position(&self, x: f64, y: f64) {
+        self.data.borrow_mut().scroll_position = (x, y);
+    }
+
+    /// Get the push, replace, go and scroll calls made so far.
+    #[must_use]
+    pub fn calls(&self) -> Vec<HistoryCall> {
+        self.data.borrow().calls.clone()
+    }
+}
+
+impl HistoryBackend for MemoryHistory {
+    fn push(&self, url: &Url) {
+        let mut data = self.data.borrow_mut();
+        data.calls.push(HistoryCall::Push(url.clone()));
+        let next = data.current + 1;
+        data.entries.truncate(next);
+        data.entries.push((url.clone(), None));
+        data.current = next;
+    }
+
+    fn replace(&self, url: &Url) {
+        let mut data = self.data.borrow_mut();
+        data.calls.push(HistoryCall::Replace(url.clone()));
+        let current = data.current;
+        data.entries[current].0 = url.clone();
+    }
+
+    fn go(&self, delta: i32) {
+        let mut data = self.data.borrow_mut();
+        data.calls.push(HistoryCall::Go(delta));
+        // As in the browser, going out of the entries does nothing.
+        let steps = usize::try_from(delta.unsigned_abs()).unwrap_or(usize::MAX);
+        let next = if delta < 0 {
+            data.current.checked_sub(steps)
+        } else {
+            data.current.checked_add(steps)
+        };
+        if let Some(next) = next.filter(|next| *next < data.entries.len()) {
+            data.current = next;
+        }
+    }
+
+    fn history_index(&self) -> Option<usize> {
+        let data = self.data.borrow();
+        data.entries[data.current].1
+    }
+
+    fn set_history_index(&self, index: usize) {
+        let mut data = self.data.borrow_mut();
+        let current = data.current;
+        data.entries[current].1 = Some(index);
+    }
+
+    fn scroll_position(&self) -> (f64, f64) {
+        self.data.borrow().scroll_position
+    }
+
+    fn scroll_to(&self, scroll: &Scroll) {
+        let mut data = self.data.borrow_mut();
+        data.calls.push(HistoryCall::Scroll(scroll.clone()));
+        if let Scroll::Position(x, y) = scroll {
+            data.scroll_position = (*x, *y);
+        }
+    }
+}
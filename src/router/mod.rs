@@ -1,20 +1,34 @@
 //! The router is responsible for finding the matching route with the current
 //! url displayed in the web browser and manage a simple history of navigation.
+mod behavior;
+mod browser;
 mod default_route;
+mod guard;
+mod history;
 mod model;
 mod path;
+mod settings;
+mod storage;
 mod url;
 mod view;
-use seed::{log, Url};
-use std::fmt::Debug;
+#[cfg(target_arch = "wasm32")]
+use seed::log;
+use seed::Url;
+use std::{convert::TryFrom, fmt::Debug};
 
+pub use behavior::*;
+pub use browser::*;
 pub use default_route::*;
+pub use guard::*;
+pub use history::*;
 pub use model::*;
 pub use path::*;
 use seed::prelude::{
     wasm_bindgen::__rt::std::{cell::RefCell, rc::Rc},
-    SubHandle,
+    Orders, SubHandle,
 };
+pub use settings::*;
+pub use storage::*;
 pub use url::*;
 pub use view::*;
 
@@ -43,7 +57,9 @@ pub enum MoveStatus {
 /// This data can mutated while the router does not so we can use it as global
 /// variable in our Seed app.
 #[allow(clippy::module_name_repetitions)]
-pub struct RouterData<Routes: Debug + PartialEq + ParsePath + Clone + Default + ParseUrl> {
+pub struct RouterData<
+    Routes: Debug + PartialEq + ParsePath + RouteBehavior + Clone + Default + ParseUrl,
+> {
     /// The actual route, which should be the one displaying the view in Seed.
     pub current_route: Routes,
     /// The index of the history.
@@ -62,14 +78,56 @@ pub struct RouterData<Routes: Debug + PartialEq + ParsePath + Clone + Default +
     pub sub_handle: Option<SubHandle>,
     /// The full history with all the routes the user has visited.
     history: Vec<Routes>,
+    /// The scroll position of each route of the history when the user left
+    /// it.
+    scroll_positions: Vec<Option<(f64, f64)>>,
+    /// The history of the browser the router follows.
+    history_backend: Rc<dyn HistoryBackend>,
+    /// The maximum number of routes kept in the history, the oldest ones are
+    /// dropped first. `None` keeps all of them.
+    max_history_length: Option<usize>,
+    /// The storage the history is saved in to restore it after a reload, if
+    /// any.
+    history_storage: Option<Rc<dyn HistoryStorage>>,
+    /// Where the routes are read from and written to in the url.
+    routing_mode: RoutingMode,
+    /// The path the app is served under, without leading and trailing `/`.
+    base_path: String,
+    /// Whether `Router::init_current_route` initialised the current route
+    /// since the router went to it.
+    current_route_initialised: bool,
 }
 
-impl<Routes: Debug + PartialEq + ParsePath + Clone + Default + ParseUrl> RouterData<Routes> {
-    /// Add the route to the history.
+impl<Routes: Debug + PartialEq + ParsePath + RouteBehavior + Clone + Default + ParseUrl>
+    RouterData<Routes>
+{
+    /// Add the route to the history after the current one.
+    /// As in a web browser, the routes after the current one are dropped.
     pub fn push_to_history(&mut self, route: Routes) {
+        self.history.truncate(self.current_history_index + 1);
+        self.scroll_positions
+            .truncate(self.current_history_index + 1);
         self.history.push(route);
+        self.scroll_positions.push(None);
+        if let Some(max) = self.max_history_length {
+            let extra = self.history.len().saturating_sub(max.max(1));
+            self.history.drain(..extra);
+            self.scroll_positions.drain(..extra);
+        }
         self.current_history_index = self.history.len() - 1;
     }
+
+    /// Replace the current route in the history, or add it when the history is
+    /// empty.
+    pub fn replace_in_history(&mut self, route: Routes) {
+        match self.history.get_mut(self.current_history_index) {
+            Some(current) => {
+                *current = route;
+                self.scroll_positions[self.current_history_index] = None;
+            }
+            None => self.push_to_history(route),
+        }
+    }
     /// Check the current page is the last one in the history.
     #[must_use]
     pub fn is_on_last_index(&self) -> bool {
@@ -88,7 +146,9 @@ impl<Routes: Debug + PartialEq + ParsePath + Clone + Default + ParseUrl> RouterD
 /// Can go back and forward,
 /// Manage the default route and current route.
 #[derive(Clone)]
-pub struct Router<Routes: Debug + PartialEq + ParsePath + Clone + Default + ParseUrl> {
+pub struct Router<
+    Routes: Debug + PartialEq + ParsePath + RouteBehavior + Clone + Default + ParseUrl,
+> {
     data: Rc<RefCell<RouterData<Routes>>>,
 }
 
@@ -96,23 +156,85 @@ pub struct Router<Routes: Debug + PartialEq + ParsePath + Clone + Default + Pars
 /// This specific mutability allows us to use the router as a global variable
 /// that we can use everywhere in the app. More information here [https://doc.rust-lang.org/book/ch15-05-interior-mutability.html](https://doc.rust-lang.org/book/ch15-05-interior-mutability.html)
 #[allow(clippy::new_without_default)]
-impl<Routes: 'static + Debug + PartialEq + ParsePath + Default + Clone + ParseUrl> Router<Routes> {
+impl<
+        Routes: 'static + Debug + PartialEq + ParsePath + RouteBehavior + Default + Clone + ParseUrl,
+    > Router<Routes>
+{
     /// Create a new Router with no url, no history and current route is default
     /// route.
+    /// It follows the history of the browser, or a `MemoryHistory` when not
+    /// built for wasm.
     pub fn new() -> Self {
         Self {
             data: Rc::new(RefCell::new(RouterData {
                 current_history_index: 0,
                 default_route: Routes::default(),
                 history: Vec::new(),
+                scroll_positions: Vec::new(),
                 current_route: Routes::default(),
                 base_url: Url::new(), // should replace with current ,maybe ?
                 current_move: MoveStatus::Ready,
                 sub_handle: None,
+                history_backend: default_history_backend(),
+                max_history_length: None,
+                history_storage: None,
+                routing_mode: RoutingMode::Path,
+                base_path: String::new(),
+                current_route_initialised: false,
             })),
         }
     }
 
+    /// Create a new Router reading and writing the routes in the url with the
+    /// given `RoutingMode`, such as `RoutingMode::Hash` for `/#/dashboard`.
+    ///
+    /// The router converts the urls it gets and `Router::url_for` gives the
+    /// urls of the routes in this mode, so the route enums do not change.
+    pub fn with_routing_mode(mode: RoutingMode) -> Self {
+        let router = Self::new();
+        router.update_data(|data| data.routing_mode = mode);
+        router
+    }
+
+    /// Serve the routes under a base path, such as `apps/crm` for an app at
+    /// `/apps/crm/`. `Router::url_for` prepends it to the urls of the routes
+    /// and the router strips it from the urls it gets before parsing them.
+    #[must_use]
+    pub fn with_base_path(self, base_path: &str) -> Self {
+        self.update_data(|data| data.base_path = base_path.trim_matches('/').to_string());
+        self
+    }
+
+    /// Keep at most `max` routes in the history, the oldest ones being dropped
+    /// first, so long sessions do not grow the memory without bound.
+    #[must_use]
+    pub fn with_max_history_length(self, max: usize) -> Self {
+        self.update_data(|data| data.max_history_length = Some(max));
+        self
+    }
+
+    /// Save the history in the given storage, such as `SessionStorage`, and
+    /// restore it in `Router::init` so the history survives a reload of the
+    /// page. The routes are saved with their url.
+    #[must_use]
+    pub fn with_history_storage(self, storage: impl HistoryStorage + 'static) -> Self {
+        self.update_data(|data| data.history_storage = Some(Rc::new(storage)));
+        self
+    }
+
+    /// Follow the given history instead of the history of the browser, such
+    /// as a `MemoryHistory` to test navigation without a browser.
+    #[must_use]
+    pub fn with_history_backend(self, backend: impl HistoryBackend + 'static) -> Self {
+        self.update_data(|data| data.history_backend = Rc::new(backend));
+        self
+    }
+
+    /// Get the history the router follows.
+    fn history_backend(&self) -> Rc<dyn HistoryBackend> {
+        self.map_data(|data| Rc::clone(&data.history_backend))
+    }
+
     /// Update the data on `RouterData` with the use of a closure.
     fn update_data(&self, updater: impl FnOnce(&mut RouterData<Routes>)) {
         updater(&mut self.data.borrow_mut());
@@ -156,7 +278,18 @@ impl<Routes: 'static + Debug + PartialEq + ParsePath + Default + Clone + ParseUr
     /// ```
     pub fn init(&self, url: Url) -> &Self {
         self.set_base_url(&url);
-        self.navigate_to_url(url);
+        self.restore_history();
+        let route = self.route_for_url(url);
+        let index = self.current_history_index();
+        // After a reload, the url is the one of the current route restored.
+        if self.with_history(|history| history.get(index) == Some(&route)) {
+            self.go_to_history_index(index);
+        } else {
+            // The browser is already on the url.
+            self.go_to_new(route, None);
+        }
+        self.history_backend()
+            .set_history_index(self.current_history_index());
         self
     }
 
@@ -206,62 +339,101 @@ impl<Routes: 'static + Debug + PartialEq + ParsePath + Default + Clone + ParseUr
     }
 
     /// Push the route to the history so you can go back to it later.
+    /// The index of the route is stored in the current browser history entry
+    /// right away, so a move of the browser before the navigation is confirmed
+    /// does not read the index of the previous route.
     fn push_to_history(&self, route: Routes) {
         self.update_data(|data| data.push_to_history(route));
+        self.save_history();
+        self.history_backend()
+            .set_history_index(self.current_history_index());
+    }
+
+    /// Save the urls of the history and the current index in the history
+    /// storage, if any.
+    fn save_history(&self) {
+        if let Some(storage) = self.map_data(|data| data.history_storage.clone()) {
+            let urls = self.with_history(|history| {
+                history
+                    .iter()
+                    .map(|route| url_to_path(&route.to_url()))
+                    .collect::<Vec<_>>()
+            });
+            if let Ok(saved) = serde_json::to_string(&(self.current_history_index(), urls)) {
+                storage.set_item(HISTORY_STORAGE_KEY, &saved);
+            }
+        }
+    }
+
+    /// Restore the history saved in the history storage, if any. A url which
+    /// does not match any route anymore gets the default route.
+    fn restore_history(&self) {
+        let saved = self
+            .map_data(|data| data.history_storage.clone())
+            .and_then(|storage| storage.get_item(HISTORY_STORAGE_KEY))
+            .and_then(|saved| serde_json::from_str::<(usize, Vec<String>)>(&saved).ok());
+        if let Some((index, urls)) = saved {
+            let history = urls
+                .iter()
+                .map(|url| {
+                    path_to_url(url)
+                        .ok()
+                        .map(|url| self.route_for_route_url(url))
+                })
+                .collect::<Option<Vec<_>>>();
+            match history {
+                Some(history) if index < history.len() => self.update_data(|data| {
+                    data.scroll_positions = vec![None; history.len()];
+                    data.history = history;
+                    data.current_history_index = index;
+                }),
+                _ => log_navigation("the saved history is not valid"),
+            }
+        }
+    }
+
+    /// If a `Routes` exists in history `delta` steps from the current one,
+    /// back with a negative `delta` and forward with a positive one, return
+    /// it. Otherwise return `None`.
+    #[must_use]
+    pub fn peek(&self, delta: isize) -> Option<Routes> {
+        let index = self.index_from_current(delta)?;
+        self.map_data(|data| data.history.get(index).cloned())
     }
 
     /// If a previous `Routes` in history exists, return it. Otherwise return
     /// `None`.
-    /// # Panics
-    /// The method will panic if the next index and history have mismatch.
     #[must_use]
     pub fn peek_back(&self) -> Option<Routes> {
-        // If we have no history, cannot go back
-
-        if self.map_data(|data| data.history.is_empty()) {
-            return None;
-        }
-        // If the current route is at index 0, we cannot go back more
-        if self.map_data(RouterData::is_on_first_index) {
-            return None;
-        }
-        let next_index: usize = self.map_data(|data| data.current_history_index - 1);
-        let history = &self.map_data(|data| data.history.clone());
-        let route = history.get(next_index).unwrap_or_else(|| {
-            panic!(
-                "We should have get route but index is failed {}",
-                next_index
-            )
-        });
-
-        Some(route.clone())
+        self.peek(-1)
     }
 
     /// If a next `Routes` in history exists, return it. Otherwise return `None`
-    /// # Panics
-    /// The method will panic if the next index and history have mismatch.
     #[must_use]
     pub fn peek_forward(&self) -> Option<Routes> {
-        // if there is no route, cannot go forward
-        if self.map_data(|data| data.history.is_empty()) {
-            return None;
-        }
-        // If we are on the last index, we cannot go forward neither
-        if self.map_data(RouterData::is_on_last_index) {
-            return None;
-        }
-        let next_index = self.map_data(|data| data.current_history_index + 1);
-
-        let history = &self.map_data(|data| data.history.clone());
+        self.peek(1)
+    }
 
-        let route = history.get(next_index).unwrap_or_else(|| {
-            panic!(
-                "We should have get route but index is failed {}",
-                next_index
-            )
-        });
+    /// Same as `Router::peek`, with the addition of navigating to a resulting
+    /// `Some(Routes)`, so `go(-2)` goes back two steps at once.
+    /// The browser history moves by the same steps, and `confirm_navigation`
+    /// matches its entry with the route when it notifies the change.
+    ///
+    /// ### Note it does not add to history since we navigate inside.
+    pub fn go(&self, delta: isize) -> Option<Routes> {
+        let backend_delta = i32::try_from(delta).ok()?;
+        let next_route = self.move_in_history(delta)?;
+        self.history_backend().go(backend_delta);
+        Some(next_route)
+    }
 
-        Some(route.clone())
+    /// Move `delta` steps in the history, without moving the browser history
+    /// which is already on the entry of the route.
+    fn move_in_history(&self, delta: isize) -> Option<Routes> {
+        let index = self.index_from_current(delta)?;
+        let next_route = self.map_data(|data| data.history.get(index).cloned())?;
+        self.go_to_history_index(index);
+        Some(next_route)
     }
 
     /// Same as `Router::peek_back`, with the addition of navigating to a
@@ -269,11 +441,7 @@ impl<Routes: 'static + Debug + PartialEq + ParsePath + Default + Clone + ParseUr
     ///
     ///   ### Note for now it does not add to history since we navigate inside.
     pub fn back(&self) -> Option<Routes> {
-        self.peek_back().map(|next_route| {
-            self.set_current_route(&next_route);
-            self.update_data(|data| data.current_history_index -= 1);
-            next_route
-        })
+        self.go(-1)
     }
 
     /// Same as `Router::peek_forward`, with the addition of navigating to a
@@ -281,57 +449,220 @@ impl<Routes: 'static + Debug + PartialEq + ParsePath + Default + Clone + ParseUr
     ///
     /// ### Note for now it does not add to history since we navigate inside.
     pub fn forward(&self) -> Option<Routes> {
-        self.peek_forward().map(|next_route| {
-            self.set_current_route(&next_route);
-            self.update_data(|data| data.current_history_index += 1);
-            next_route
-        })
+        self.go(1)
+    }
+
+    /// Get the index in the history `delta` steps from the current one, if it
+    /// is not before the first route.
+    fn index_from_current(&self, delta: isize) -> Option<usize> {
+        let current = self.current_history_index();
+        if delta < 0 {
+            current.checked_sub(delta.unsigned_abs())
+        } else {
+            current.checked_add(delta.unsigned_abs())
+        }
     }
 
     /// Check the route is the current route.
     /// Could be use directly with url as well.
     #[must_use]
     pub fn is_current_route(&self, route: &Routes) -> bool {
-        route.eq(&self.current_route())
+        self.with_current_route(|current_route| route.eq(current_route))
     }
 
     /// Go to the next url with the associated route.
-    /// This will push to history. So If you go back multiple time and then use
-    /// navigate and then go back, you will not get the previous page, but the
-    /// one just pushed into history before.
+    /// This will push to history, and push the url of the route to the
+    /// browser history. As in a web browser, if you go back multiple times and
+    /// then navigate, the routes you went back from are dropped and going back
+    /// gets you to the route you navigated from.
     pub fn navigate_to_new(&self, route: Routes) {
+        let url = self.url_for(&route);
+        self.go_to_new(route, Some(&url));
+    }
+
+    /// Go to the route and push it to the history. The url is pushed to the
+    /// browser history when given, it is already there when the browser or
+    /// Seed changed it.
+    fn go_to_new(&self, route: Routes, url: Option<&Url>) {
+        self.save_scroll_position();
         self.set_current_route(&route);
+        if let Some(url) = url {
+            self.history_backend().push(url);
+        }
         self.push_to_history(route);
+        self.scroll_to_current_route(None);
+    }
+
+    /// Go to the route in place of the current one, without pushing to the
+    /// history, and replace the url of the current browser history entry.
+    /// Going back will not come back to the replaced route, which suits
+    /// redirects such as login to dashboard or a url being normalised.
+    pub fn navigate_replace(&self, route: Routes) {
+        let url = self.url_for(&route);
+        self.replace_current_route(route);
+        self.history_backend().replace(&url);
+    }
+
+    /// Same as `Router::navigate_replace` with the route matching the url, or
+    /// the default route. The current browser history entry gets this url.
+    pub fn replace_url(&self, url: Url) {
+        self.history_backend().replace(&url);
+        self.replace_current_route(self.route_for_url(url));
+    }
+
+    /// Set the current route and replace it in the history.
+    fn replace_current_route(&self, route: Routes) {
+        self.set_current_route(&route);
+        self.update_data(|data| data.replace_in_history(route));
+        self.save_history();
+        self.history_backend()
+            .set_history_index(self.current_history_index());
+        self.scroll_to_current_route(None);
     }
 
     /// Match the url that change and update the router with the new current
     /// Routes.
-    pub fn navigate_to_url(&self, url: Url) {
-        if let Ok(route_match) = Routes::from_url(url) {
-            log!("found route");
-            self.navigate_to_new(route_match);
-        } else {
-            let default = self.default_route();
-            self.navigate_to_new(default);
+    ///
+    /// If the url does not match any route, the error is logged and the router
+    /// goes to the default route, filled with the url when it carries it.
+    /// The url is pushed to the browser history.
+    ///
+    /// The guards of the route are checked with the model before, so a route
+    /// they do not allow never gets in the history or the url. A redirect goes
+    /// to the route redirected to, with its url, and a denied route is not
+    /// navigated to. A pending route is, `Router::init_current_route` checks
+    /// it again once the model changed.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn navigate_to_url<Model, Msg>(&self, url: Url, model: &Model)
+    where
+        Routes: Guarded<Model, Msg>,
+    {
+        let route = self.route_for_url(url.clone());
+        match resolve_guards::<_, _, Msg>(route.clone(), model) {
+            (_, GuardResult::Deny(_)) => log_navigation("the guards deny the route"),
+            (resolved, _) if resolved == route => self.go_to_new(route, Some(&url)),
+            (resolved, _) => self.navigate_to_new(resolved),
+        }
+    }
+
+    /// Check the guards of the current route with the model and initialise
+    /// the route, to call when the url changed in place of `Init::init`.
+    ///
+    /// When the guards redirect, the route redirected to replaces the current
+    /// one in the history and the url, as with `Router::navigate_replace`, and
+    /// is initialised instead. The redirects are followed as with
+    /// `resolve_guards`. A denied or pending route is not initialised.
+    ///
+    /// Call it again when the model the guards read changes, such as when the
+    /// user is loaded, to check the guards again. The current route is then
+    /// initialised if they allow it now, and only once until the router goes
+    /// to another route.
+    pub fn init_current_route<Model, Msg: 'static>(
+        &self,
+        model: &mut Model,
+        orders: &mut impl Orders<Msg>,
+    ) where
+        Routes: Init<Routes, Model, Msg> + Guarded<Model, Msg>,
+    {
+        if self.check_current_route::<Model, Msg>(model) {
+            self.current_route().init(model, orders);
+        }
+    }
+
+    /// Resolve the guards of the current route with the model and replace it
+    /// with the route they redirect to. Return whether this route is allowed
+    /// and was not initialised yet.
+    fn check_current_route<Model, Msg>(&self, model: &Model) -> bool
+    where
+        Routes: Guarded<Model, Msg>,
+    {
+        let (route, result) = resolve_guards::<_, _, Msg>(self.current_route(), model);
+        if !self.is_current_route(&route) {
+            self.navigate_replace(route);
+        }
+        let allowed = matches!(result, GuardResult::Allow);
+        let initialised = self.map_data(|data| data.current_route_initialised);
+        self.update_data(|data| data.current_route_initialised = allowed);
+        allowed && !initialised
+    }
+
+    /// Get the url of the route, with the `RoutingMode` and the base path of
+    /// the router, to use in the links of the app.
+    pub fn url_for(&self, route: &Routes) -> Url {
+        self.url_for_route_url(&route.to_url())
+    }
+
+    /// Get the url of the url of a route, as given by `ParseUrl::to_url`, with
+    /// the `RoutingMode` and the base path of the router.
+    fn url_for_route_url(&self, route_url: &Url) -> Url {
+        // The path of a `Url` is encoded again by `url_to_path`, so it always
+        // decodes.
+        let route_path = url_to_path(route_url);
+        self.map_data(|data| route_path_to_url(&route_path, data.routing_mode, &data.base_path))
+            .unwrap_or_else(|_| route_url.clone())
+    }
+
+    /// Get the route matching the url, or the default route.
+    #[allow(clippy::needless_pass_by_value)]
+    fn route_for_url(&self, url: Url) -> Routes {
+        let route_url = self
+            .map_data(|data| url_to_route_path(&url, data.routing_mode, &data.base_path))
+            .and_then(|route_path| path_to_url(&route_path));
+        match route_url {
+            Ok(route_url) => self.route_for_route_url(route_url),
+            Err(err) => {
+                log_navigation(&format!(
+                    "no route found, going to the default route: {}",
+                    err
+                ));
+                Routes::default_route_for(&url).unwrap_or_else(|| self.default_route())
+            }
+        }
+    }
+
+    /// Get the route matching the url of a route, as given by
+    /// `ParseUrl::to_url`, or the default route.
+    fn route_for_route_url(&self, url: Url) -> Routes {
+        let default_route_for_url = Routes::default_route_for(&url);
+        match Routes::from_url(url) {
+            Ok(route_match) => {
+                log_navigation("found route");
+                route_match
+            }
+            Err(err) => {
+                log_navigation(&format!(
+                    "no route found, going to the default route: {}",
+                    err
+                ));
+                default_route_for_url.unwrap_or_else(|| self.default_route())
+            }
         }
     }
 
     /// Ask Seed the new request url back in history.
     pub fn request_moving_back<F: FnOnce(Url) -> R, R>(&self, func: F) {
-        self.peek_back().map(|next_route| {
+        self.url_from_current(-1).map(|next_url| {
             self.update_data(|data| data.current_move = MoveStatus::MovingBack);
-            func(next_route.to_url())
+            func(next_url)
         });
     }
 
     /// Ask Seed the new request url forward in history.
     pub fn request_moving_forward<F: FnOnce(Url) -> R, R>(&self, func: F) {
-        self.peek_forward().map(|next_route| {
+        self.url_from_current(1).map(|next_url| {
             self.update_data(|data| data.current_move = MoveStatus::MovingForward);
-            func(next_route.to_url())
+            func(next_url)
         });
     }
 
+    /// Get the url of the route `delta` steps from the current one in history,
+    /// without cloning the route.
+    fn url_from_current(&self, delta: isize) -> Option<Url> {
+        let index = self.index_from_current(delta)?;
+        let route_url = self.with_history(|history| history.get(index).map(ParseUrl::to_url))?;
+        Some(self.url_for_route_url(&route_url))
+    }
+
     /// This method accepts a given url and chooses the appropriate update for
     /// the history depending of the `MoveStatus`.
     /// It also resets the current move to Ready.
@@ -371,33 +702,120 @@ impl<Routes: 'static + Debug + PartialEq + ParsePath + Default + Clone + ParseUr
     ///     NotFound
     /// }
     /// ```
+    ///
+    /// The index of the route in the router history is stored in the state of
+    /// the browser history entry, so going back or forward with the buttons of
+    /// the browser moves in the router history as well, when the url still
+    /// matches the route at this index.
     pub fn confirm_navigation(&self, url: Url) {
-        log!("ask route");
+        log_navigation("ask route");
+        let history_backend = self.history_backend();
         match self.map_data(|data| data.current_move.clone()) {
             MoveStatus::Navigating | MoveStatus::Ready => {
-                self.navigate_to_url(url);
+                let route = self.route_for_url(url);
+                match history_backend.history_index() {
+                    // The browser moved back or forward to a route of the history.
+                    Some(index)
+                        if self.map_data(|data| data.history.get(index) == Some(&route)) =>
+                    {
+                        self.go_to_history_index(index);
+                    }
+                    _ => self.go_to_new(route, None),
+                }
             }
+            // Seed pushed the requested url, the browser history is on it.
             MoveStatus::MovingBack => {
-                self.back();
+                self.move_in_history(-1);
             }
             MoveStatus::MovingForward => {
-                self.forward();
+                self.move_in_history(1);
             }
         }
         self.update_data(|data| data.current_move = MoveStatus::Ready);
+        history_backend.set_history_index(self.current_history_index());
+    }
+
+    /// Go to the route at the given index in the history without pushing it.
+    /// The scroll position the route had when the user left it is restored.
+    fn go_to_history_index(&self, index: usize) {
+        let moving = index != self.current_history_index();
+        if moving {
+            self.save_scroll_position();
+        }
+        self.update_data(|data| {
+            data.current_route = data.history[index].clone();
+            data.current_history_index = index;
+            data.current_route_initialised &= !moving;
+        });
+        self.save_history();
+        if moving {
+            let saved = self.map_data(|data| data.scroll_positions.get(index).copied().flatten());
+            self.scroll_to_current_route(saved);
+        }
+    }
+
+    /// Save the scroll position of the current route before leaving it.
+    fn save_scroll_position(&self) {
+        let position = self.history_backend().scroll_position();
+        self.update_data(|data| {
+            if let Some(saved) = data.scroll_positions.get_mut(data.current_history_index) {
+                *saved = Some(position);
+            }
+        });
+    }
+
+    /// Scroll to the saved position of the current route, or to its fragment
+    /// or the top of the page when there is none, unless the route has
+    /// `#[no_scroll_restoration]`.
+    fn scroll_to_current_route(&self, saved: Option<(f64, f64)>) {
+        let scroll = self.with_current_route(|route| {
+            if !route.restores_scroll() {
+                return None;
+            }
+            Some(match (saved, route.fragment()) {
+                (Some((x, y)), _) => Scroll::Position(x, y),
+                (None, Some(fragment)) => Scroll::Fragment(fragment.to_string()),
+                (None, None) => Scroll::Position(0., 0.),
+            })
+        });
+        if let Some(scroll) = scroll {
+            self.history_backend().scroll_to(&scroll);
+        }
     }
 
     /// Set the current route of the router. It should be used only privately.
     fn set_current_route(&self, route: &Routes) {
-        self.update_data(|data| data.current_route = route.clone());
+        self.update_data(|data| {
+            data.current_route = route.clone();
+            data.current_route_initialised = false;
+        });
     }
 
     /// Get the current route of the router.
+    /// `Router::with_current_route` reads it without cloning it.
     #[must_use]
     pub fn current_route(&self) -> Routes {
         self.map_data(|data| data.current_route.clone())
     }
 
+    /// Call `reader` with a reference to the current route, without cloning
+    /// it, and return its result.
+    /// # Panics
+    /// The router is borrowed during the call, so `reader` panics if it
+    /// updates the router, by navigating for example.
+    pub fn with_current_route<T>(&self, reader: impl FnOnce(&Routes) -> T) -> T {
+        self.map_data(|data| reader(&data.current_route))
+    }
+
+    /// Call `reader` with the routes of the history, from the oldest one,
+    /// without cloning them, and return its result.
+    /// # Panics
+    /// The router is borrowed during the call, so `reader` panics if it
+    /// updates the router, by navigating for example.
+    pub fn with_history<T>(&self, reader: impl FnOnce(&[Routes]) -> T) -> T {
+        self.map_data(|data| reader(&data.history))
+    }
+
     /// Get the default route of the router. The default route is used when an
     /// Url does not match the given Routes.
     #[must_use]
@@ -417,6 +835,28 @@ impl<Routes: 'static + Debug + PartialEq + ParsePath + Default + Clone + ParseUr
     }
 }
 
+/// The history of the browser, or a history in memory for native tests which
+/// have no browser.
+fn default_history_backend() -> Rc<dyn HistoryBackend> {
+    if cfg!(target_arch = "wasm32") {
+        Rc::new(BrowserHistory)
+    } else {
+        Rc::new(MemoryHistory::new())
+    }
+}
+
+/// The key of the history in the history storage.
+const HISTORY_STORAGE_KEY: &str = "seed_routing_history";
+
+/// Log the navigation in the console of the browser. Native tests have no
+/// console.
+fn log_navigation(message: &str) {
+    #[cfg(target_arch = "wasm32")]
+    log!(message);
+    #[cfg(not(target_arch = "wasm32"))]
+    let _ = message;
+}
+
 #[cfg(test)]
 mod test {
     use seed::Url;
@@ -446,6 +886,7 @@ mod test {
     }
 
     #[derive(Debug, PartialEq, Clone, ParseUrl, WithDefaultRoute)]
+    #[custom_guard]
     enum ExampleRoutes {
         Login,
         Register,
@@ -457,7 +898,29 @@ mod test {
         Home,
     }
 
-    #[wasm_bindgen_test]
+    #[derive(Debug, PartialEq, Clone, ParseUrl, WithDefaultRoute)]
+    enum ScrollRoutes {
+        Articles,
+        Article {
+            id: u32,
+            fragment: Option<String>,
+        },
+        #[no_scroll_restoration]
+        Chat,
+        #[default_route]
+        NotFound,
+    }
+
+    #[derive(Debug, PartialEq, Clone, ParseUrl, WithDefaultRoute)]
+    enum LostRoutes {
+        Login,
+        #[default_route]
+        NotFound {
+            path: String,
+        },
+    }
+
+    #[test]
     fn test_new_router() {
         let router = Router::<ExampleRoutes>::new();
         let current = router.current_route();
@@ -474,7 +937,7 @@ mod test {
         assert!(!router_data.is_on_last_index());
     }
 
-    #[wasm_bindgen_test]
+    #[test]
     fn test_init_router() {
         let router = Router::<ExampleRoutes>::new();
         // We should get the right route when the Url is valid
@@ -490,7 +953,7 @@ mod test {
         }
         // We should get default route when the Url does not match a route
         {
-            let url: Url = "http://localhost/profile".parse().unwrap();
+            let url = Url::new().set_path(["profile"]);
             let router = router.init(url.clone());
             let current = router.current_route();
             let router_data = router.data.borrow();
@@ -499,7 +962,7 @@ mod test {
         }
     }
 
-    #[wasm_bindgen_test]
+    #[test]
     fn test_set_current_route() {
         let router = Router::<ExampleRoutes>::new();
         assert_eq!(router.current_route(), ExampleRoutes::NotFound);
@@ -507,17 +970,17 @@ mod test {
         assert_eq!(router.current_route(), ExampleRoutes::Login);
     }
 
-    #[wasm_bindgen_test]
+    #[test]
     fn test_set_base_url() {
         let router = Router::<ExampleRoutes>::new();
 
-        let url: Url = "http://localhost/profile".parse().unwrap();
+        let url = Url::new().set_path(["profile"]);
         let router = router.set_base_url(&url);
         let router_data = router.data.borrow();
-        assert_eq!(router_data.base_url, "http://localhost".parse().unwrap());
+        assert_eq!(router_data.base_url, Url::new());
     }
 
-    #[wasm_bindgen_test]
+    #[test]
     fn test_push_to_history() {
         let router = Router::<ExampleRoutes>::new();
         let route = ExampleRoutes::Dashboard(DashboardRoutes::Profile(23));
@@ -527,7 +990,7 @@ mod test {
         assert_eq!(history.get(0).unwrap(), &route);
     }
 
-    #[wasm_bindgen_test]
+    #[test]
     fn test_peek_back() {
         let router = Router::<ExampleRoutes>::new();
         let previous_route = router.peek_back();
@@ -543,7 +1006,7 @@ mod test {
         );
     }
 
-    #[wasm_bindgen_test]
+    #[test]
     fn test_peek_forward() {
         let router = Router::<ExampleRoutes>::new();
         let next_route = router.peek_forward();
@@ -562,7 +1025,7 @@ mod test {
         );
     }
 
-    #[wasm_bindgen_test]
+    #[test]
     fn test_request_moving_back() {
         let router = Router::<ExampleRoutes>::new();
         let route_1 = ExampleRoutes::Dashboard(DashboardRoutes::Profile(23));
@@ -590,7 +1053,7 @@ mod test {
     }
 
     // Also tests is_on_last_index
-    #[wasm_bindgen_test]
+    #[test]
     fn test_request_moving_forward() {
         let router = Router::<ExampleRoutes>::new();
         let route_1 = ExampleRoutes::Dashboard(DashboardRoutes::Profile(23));
@@ -629,7 +1092,7 @@ mod test {
         }
     }
 
-    #[wasm_bindgen_test]
+    #[test]
     fn test_router_default_route() {
         let router = Router::<ExampleRoutes>::new();
         let current = router.current_route();
@@ -637,11 +1100,293 @@ mod test {
         assert_eq!(current, default);
     }
 
+    #[test]
+    fn test_navigate_to_bad_url() {
+        let router: Router<ExampleRoutes> = Router::new();
+        router.navigate_to_new(ExampleRoutes::Login);
+
+        let url = router
+            .base_url()
+            .set_path("dashboard/profile/not_a_number".split('/'));
+        router.navigate_to_url(url, &true);
+        assert_eq!(router.current_route(), ExampleRoutes::NotFound);
+
+        let url = router.base_url().set_path("dashboard/garbage".split('/'));
+        router.navigate_to_url(url, &true);
+        assert_eq!(router.current_route(), ExampleRoutes::NotFound);
+    }
+
+    #[test]
+    fn test_hash_routing_mode() {
+        let history = MemoryHistory::new();
+        let router: Router<ExampleRoutes> =
+            Router::with_routing_mode(RoutingMode::Hash).with_history_backend(history.clone());
+        router.init(Url::new().set_hash("/dashboard/profile/3"));
+        let current = router.current_route();
+
+        router.navigate_to_url(Url::new().set_hash("/login"), &true);
+        let login = router.current_route();
+        // The path of the url is ignored.
+        router.navigate_to_url(Url::new().set_path(["register"]), &true);
+        let home = router.current_route();
+        router.navigate_replace(ExampleRoutes::Stuff);
+
+        assert_eq!(
+            current,
+            ExampleRoutes::Dashboard(DashboardRoutes::Profile(3))
+        );
+        assert_eq!(login, ExampleRoutes::Login);
+        assert_eq!(home, ExampleRoutes::Home);
+        assert_eq!(
+            router.url_for(&ExampleRoutes::Login),
+            Url::new().set_hash("/login")
+        );
+        assert_eq!(
+            history.calls().last(),
+            Some(&HistoryCall::Replace(Url::new().set_hash("/stuff")))
+        );
+        // The mode belongs to the router, the routes do not change.
+        assert_eq!(
+            Router::<ExampleRoutes>::new().url_for(&ExampleRoutes::Login),
+            ExampleRoutes::Login.to_url()
+        );
+    }
+
+    #[test]
+    fn test_base_path() {
+        let router: Router<ExampleRoutes> = Router::new()
+            .with_base_path("/apps/crm/")
+            .with_history_backend(MemoryHistory::new());
+        assert_eq!(router.data.borrow().base_path, "apps/crm");
+        router.init(
+            router
+                .base_url()
+                .set_path("apps/crm/dashboard/admin/other".split('/')),
+        );
+        let nested = router.current_route();
+
+        router.navigate_to_url(
+            router.base_url().set_path("apps/crm/login".split('/')),
+            &true,
+        );
+        let login = router.current_route();
+        // A url outside of the base path does not match.
+        router.navigate_to_url(router.base_url().set_path("login".split('/')), &true);
+        let outside = router.current_route();
+
+        assert_eq!(
+            nested,
+            ExampleRoutes::Dashboard(DashboardRoutes::Admin(DashboardAdminRoutes::Other))
+        );
+        assert_eq!(login, ExampleRoutes::Login);
+        assert_eq!(outside, ExampleRoutes::NotFound);
+        assert_eq!(
+            router.url_for(&ExampleRoutes::Login),
+            Url::new().set_path(["apps", "crm", "login"])
+        );
+    }
+
     #[wasm_bindgen_test]
+    fn test_link_with_base_path() {
+        use seed::{a, attrs, prelude::*};
+
+        let router: Router<ExampleRoutes> = Router::with_routing_mode(RoutingMode::Path)
+            .with_base_path("apps/crm")
+            .with_history_backend(MemoryHistory::new());
+        let link: Node<()> = a![
+            attrs! { At::Href => router.url_for(&ExampleRoutes::Dashboard(DashboardRoutes::Profile(3))) },
+            "Profile",
+        ];
+        assert_eq!(
+            link.to_string(),
+            r#"<a href="/apps/crm/dashboard/profile/3">Profile</a>"#
+        );
+    }
+
+    #[test]
+    fn test_browser_back_and_forward() {
+        let history = MemoryHistory::new();
+        let router: Router<ExampleRoutes> = Router::new().with_history_backend(history.clone());
+        let url = |path: &str| router.base_url().set_path(path.split('/'));
+        history.push(&url("login"));
+        router.init(history.current_url());
+        // Seed pushes the url of a link before notifying the router.
+        for path in &["register", "stuff"] {
+            history.push(&url(path));
+            router.confirm_navigation(history.current_url());
+        }
+        assert_eq!(router.current_history_index(), 2);
+
+        // The back button of the browser.
+        history.go(-1);
+        router.confirm_navigation(history.current_url());
+        assert_eq!(router.current_route(), ExampleRoutes::Register);
+        assert_eq!(router.current_history_index(), 1);
+        assert_eq!(router.peek_back(), Some(ExampleRoutes::Login));
+        assert_eq!(router.peek_forward(), Some(ExampleRoutes::Stuff));
+
+        history.go(-1);
+        router.confirm_navigation(history.current_url());
+        assert_eq!(router.current_route(), ExampleRoutes::Login);
+        assert_eq!(router.current_history_index(), 0);
+        assert_eq!(router.peek_back(), None);
+
+        // The forward button of the browser.
+        history.go(2);
+        router.confirm_navigation(history.current_url());
+        assert_eq!(router.current_route(), ExampleRoutes::Stuff);
+        assert_eq!(router.current_history_index(), 2);
+        assert_eq!(
+            history
+                .calls()
+                .iter()
+                .filter(|call| !matches!(call, HistoryCall::Scroll(_)))
+                .count(),
+            6
+        );
+    }
+
+    #[test]
+    fn test_replace_url() {
+        let history = MemoryHistory::new();
+        let router: Router<ExampleRoutes> = Router::new().with_history_backend(history.clone());
+        let url = |path: &str| router.base_url().set_path(path.split('/'));
+        history.push(&url("stuff"));
+        router.init(history.current_url());
+        history.push(&url("login"));
+        router.confirm_navigation(history.current_url());
+
+        router.replace_url(url("register"));
+        assert_eq!(router.current_route(), ExampleRoutes::Register);
+        assert_eq!(router.current_history_index(), 1);
+        assert_eq!(router.peek_back(), Some(ExampleRoutes::Stuff));
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.current_url(), url("register"));
+        assert!(history
+            .calls()
+            .contains(&HistoryCall::Replace(url("register"))));
+
+        // The back button skips the replaced route.
+        history.go(-1);
+        router.confirm_navigation(history.current_url());
+        assert_eq!(router.current_route(), ExampleRoutes::Stuff);
+        history.go(1);
+        router.confirm_navigation(history.current_url());
+        assert_eq!(router.current_route(), ExampleRoutes::Register);
+        assert_eq!(router.current_history_index(), 1);
+    }
+
+    #[test]
+    fn test_navigate_replace() {
+        let history = MemoryHistory::new();
+        let router: Router<ExampleRoutes> = Router::new().with_history_backend(history.clone());
+        router.init(router.base_url().set_path(["login"]));
+        router.navigate_to_new(ExampleRoutes::Stuff);
+
+        router.navigate_replace(ExampleRoutes::Register);
+        assert_eq!(router.current_route(), ExampleRoutes::Register);
+        assert_eq!(router.current_history_index(), 1);
+        assert_eq!(router.peek_back(), Some(ExampleRoutes::Login));
+        assert_eq!(router.peek_forward(), None);
+        assert_eq!(history.current_url(), ExampleRoutes::Register.to_url());
+        assert_eq!(history.history_index(), Some(1));
+    }
+
+    #[test]
+    fn test_history_calls() {
+        let history = MemoryHistory::with_url(ExampleRoutes::Login.to_url());
+        let router: Router<ExampleRoutes> = Router::new().with_history_backend(history.clone());
+        router.init(history.current_url());
+        router.navigate_to_new(ExampleRoutes::Stuff);
+        router.navigate_replace(ExampleRoutes::Register);
+        router.replace_url(ExampleRoutes::Dashboard(DashboardRoutes::Profile(3)).to_url());
+        router.back();
+
+        assert_eq!(
+            history.calls(),
+            vec![
+                HistoryCall::Scroll(Scroll::Position(0., 0.)),
+                HistoryCall::Push(ExampleRoutes::Stuff.to_url()),
+                HistoryCall::Scroll(Scroll::Position(0., 0.)),
+                HistoryCall::Scroll(Scroll::Position(0., 0.)),
+                HistoryCall::Replace(ExampleRoutes::Register.to_url()),
+                HistoryCall::Replace(
+                    ExampleRoutes::Dashboard(DashboardRoutes::Profile(3)).to_url()
+                ),
+                HistoryCall::Scroll(Scroll::Position(0., 0.)),
+                HistoryCall::Scroll(Scroll::Position(0., 0.)),
+                HistoryCall::Go(-1),
+            ]
+        );
+        assert_eq!(history.current_url(), ExampleRoutes::Login.to_url());
+        assert_eq!(history.history_index(), Some(0));
+    }
+
+    #[test]
+    fn test_memory_history() {
+        let history = MemoryHistory::new();
+        let url = |path: &str| Url::new().set_path(path.split('/'));
+        history.push(&url("login"));
+        history.set_history_index(0);
+        history.push(&url("register"));
+        history.replace(&url("stuff"));
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.current_url(), url("stuff"));
+        assert_eq!(history.history_index(), None);
+
+        history.go(-1);
+        assert_eq!(history.current_url(), url("login"));
+        assert_eq!(history.history_index(), Some(0));
+        // Going out of the entries does nothing.
+        history.go(-5);
+        assert_eq!(history.current_url(), url("login"));
+
+        // Pushing drops the entries after the current one.
+        history.push(&url("dashboard"));
+        assert_eq!(history.len(), 3);
+        assert_eq!(
+            history.calls(),
+            vec![
+                HistoryCall::Push(url("login")),
+                HistoryCall::Push(url("register")),
+                HistoryCall::Replace(url("stuff")),
+                HistoryCall::Go(-1),
+                HistoryCall::Go(-5),
+                HistoryCall::Push(url("dashboard")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_navigate_to_default_route_with_url() {
+        let router: Router<LostRoutes> = Router::new();
+        assert_eq!(
+            router.default_route(),
+            LostRoutes::NotFound {
+                path: String::new()
+            }
+        );
+
+        let url = router.base_url().set_path("dashboard/garbage".split('/'));
+        router.navigate_to_url::<_, ()>(url, &());
+        assert_eq!(
+            router.current_route(),
+            LostRoutes::NotFound {
+                path: "/dashboard/garbage".to_string()
+            }
+        );
+        assert!(router.current_route().is_default());
+
+        let url = router.base_url().set_path("login".split('/'));
+        router.navigate_to_url::<_, ()>(url, &());
+        assert_eq!(router.current_route(), LostRoutes::Login);
+    }
+
+    #[test]
     fn test_build_url() {
         let router: Router<ExampleRoutes> = Router::new();
         let url = router.base_url().add_path_part("");
-        router.navigate_to_url(url);
+        router.navigate_to_url(url, &true);
         assert_eq!(
             router.current_route(),
             ExampleRoutes::parse_path("").unwrap()
@@ -651,7 +1396,7 @@ mod test {
             .base_url()
             .set_path("dashboard/admin/other".split('/'));
 
-        router.navigate_to_url(admin_url);
+        router.navigate_to_url(admin_url, &true);
         assert_eq!(
             router.current_route(),
             ExampleRoutes::parse_path("/dashboard/admin/other").unwrap()
@@ -659,17 +1404,22 @@ mod test {
 
         let admin_url = router.base_url().set_path("dashboard/profile/1".split('/'));
 
-        router.navigate_to_url(admin_url);
+        router.navigate_to_url(admin_url, &true);
         assert_eq!(
             router.current_route(),
             ExampleRoutes::parse_path("/dashboard/profile/1").unwrap()
         );
     }
 
-    #[wasm_bindgen_test]
+    #[test]
     fn test_navigation_to_new() {
-        let router: Router<ExampleRoutes> = Router::new();
+        let history = MemoryHistory::new();
+        let router: Router<ExampleRoutes> = Router::new().with_history_backend(history.clone());
         router.navigate_to_new(ExampleRoutes::parse_path("/dashboard/profile/1").unwrap());
+        assert_eq!(
+            history.current_url(),
+            ExampleRoutes::Dashboard(DashboardRoutes::Profile(1)).to_url()
+        );
 
         assert_eq!(
             router.current_route(),
@@ -691,20 +1441,28 @@ mod test {
             ExampleRoutes::parse_path("").unwrap()
         );
         assert_eq!(router.current_history_index(), 2);
+        // The first entry is the empty url of the new tab.
+        assert_eq!(history.len(), 4);
+        assert_eq!(history.current_url(), ExampleRoutes::Home.to_url());
+        assert_eq!(history.history_index(), Some(2));
     }
 
-    #[wasm_bindgen_test]
+    #[test]
     fn test_navigation_to_url() {
-        let router: Router<ExampleRoutes> = Router::new();
+        let history = MemoryHistory::new();
+        let router: Router<ExampleRoutes> = Router::new().with_history_backend(history.clone());
         let url = Url::new().add_path_part("doesn't exist");
-        router.navigate_to_url(url);
+        router.navigate_to_url(url.clone(), &true);
         assert_eq!(router.current_route(), router.default_route());
+        // The url is kept in the browser history, as it was requested.
+        assert_eq!(history.current_url(), url);
         let url = Url::new().add_path_part("dashboard");
-        router.navigate_to_url(url);
+        router.navigate_to_url(url.clone(), &true);
         assert_eq!(
             router.current_route(),
             ExampleRoutes::Dashboard(DashboardRoutes::Root)
         );
+        assert!(history.calls().contains(&HistoryCall::Push(url)));
     }
 
     // Testing return value and side effects of Router::back
@@ -713,7 +1471,7 @@ mod test {
     // is_current_route() is still correct
     //
     // Also tests is_on_last_index
-    #[wasm_bindgen_test]
+    #[test]
     fn test_backward() {
         let router: Router<ExampleRoutes> = Router::new();
 
@@ -762,23 +1520,24 @@ mod test {
             true
         );
 
+        // The routes we went back from are dropped, as in a web browser.
+        assert_eq!(router.current_history_index(), 1);
+        assert_eq!(router.peek_forward(), None);
+
         let back = router.back();
         assert!(!router.data.borrow().is_on_last_index());
         assert_eq!(
             back,
-            Some(ExampleRoutes::parse_path("dashboard/admin/other").unwrap()),
-            "We should have gone back to \"dashboard/admin/other\""
+            Some(ExampleRoutes::parse_path("").unwrap()),
+            "We should have gone back to root path"
         );
         assert_eq!(back, Some(router.current_route()));
-        // Here is tricky part, after navigate we go back to the end of history, so if
-        // we go back, we go to the previous index
-        assert_eq!(router.current_history_index(), 2);
-        assert!(!router.data.borrow().is_on_last_index());
+        assert_eq!(router.current_history_index(), 0);
     }
 
     // assumes correct functioning of back() in the case of not currently at most
     // recent history
-    #[wasm_bindgen_test]
+    #[test]
     fn test_forward() {
         let router: Router<ExampleRoutes> = Router::new();
 
@@ -839,8 +1598,376 @@ mod test {
             "History index should not change after empty-action forward()"
         );
     }
+
+    #[test]
+    fn test_go_and_peek() {
+        let history = MemoryHistory::new();
+        let router: Router<ExampleRoutes> = Router::new().with_history_backend(history.clone());
+        assert_eq!(router.peek(0), None);
+        router.navigate_to_new(ExampleRoutes::Login);
+        router.navigate_to_new(ExampleRoutes::Register);
+        router.navigate_to_new(ExampleRoutes::Stuff);
+        router.navigate_to_new(ExampleRoutes::NotFound);
+
+        assert_eq!(router.peek(0), Some(ExampleRoutes::NotFound));
+        assert_eq!(router.peek(-3), Some(ExampleRoutes::Login));
+        assert_eq!(router.peek(-4), None);
+        assert_eq!(router.peek(1), None);
+
+        assert_eq!(router.go(-2), Some(ExampleRoutes::Register));
+        assert_eq!(router.current_history_index(), 1);
+        assert_eq!(history.current_url(), ExampleRoutes::Register.to_url());
+        assert_eq!(history.history_index(), Some(1));
+        assert_eq!(router.go(5), None);
+        assert_eq!(router.current_route(), ExampleRoutes::Register);
+        assert_eq!(router.go(2), Some(ExampleRoutes::NotFound));
+        assert_eq!(router.current_history_index(), 3);
+        assert_eq!(history.current_url(), ExampleRoutes::NotFound.to_url());
+        // The browser notifies the move, which the router already made.
+        router.confirm_navigation(history.current_url());
+        assert_eq!(router.current_history_index(), 3);
+
+        // The browser history moves with the router, but not out of it.
+        let moves = history
+            .calls()
+            .into_iter()
+            .filter(|call| matches!(call, HistoryCall::Go(_)))
+            .collect::<Vec<_>>();
+        assert_eq!(moves, vec![HistoryCall::Go(-2), HistoryCall::Go(2)]);
+    }
+
+    #[test]
+    fn test_max_history_length() {
+        let router: Router<ExampleRoutes> = Router::new().with_max_history_length(2);
+        router.navigate_to_new(ExampleRoutes::Login);
+        router.navigate_to_new(ExampleRoutes::Register);
+        router.navigate_to_new(ExampleRoutes::Stuff);
+
+        assert_eq!(router.map_data(|data| data.history.len()), 2);
+        assert_eq!(router.current_history_index(), 1);
+        assert_eq!(router.peek_back(), Some(ExampleRoutes::Register));
+        assert_eq!(router.go(-2), None);
+    }
+
+    #[test]
+    fn test_with_current_route_and_history() {
+        let router: Router<ExampleRoutes> = Router::new();
+        router.navigate_to_new(ExampleRoutes::Login);
+        router.navigate_to_new(ExampleRoutes::Dashboard(DashboardRoutes::Profile(3)));
+        router.back();
+
+        assert!(router.with_current_route(|route| *route == ExampleRoutes::Login));
+        assert_eq!(router.with_history(<[ExampleRoutes]>::len), 2);
+        assert_eq!(
+            router.with_history(|history| history.last().map(ExampleRoutes::is_default)),
+            Some(false)
+        );
+        assert!(router.is_current_route(&ExampleRoutes::Login));
+    }
+
+    #[test]
+    fn test_restore_history() {
+        let storage = MemoryStorage::new();
+        let router: Router<ExampleRoutes> = Router::new()
+            .with_history_storage(storage.clone())
+            .with_history_backend(MemoryHistory::new());
+        router.init(ExampleRoutes::Login.to_url());
+        router.navigate_to_new(ExampleRoutes::Register);
+        router.navigate_to_new(ExampleRoutes::Dashboard(DashboardRoutes::Profile(3)));
+        router.back();
+
+        // The page is reloaded.
+        let router: Router<ExampleRoutes> = Router::new()
+            .with_history_storage(storage.clone())
+            .with_history_backend(MemoryHistory::new());
+        router.init(ExampleRoutes::Register.to_url());
+        assert_eq!(router.current_route(), ExampleRoutes::Register);
+        assert_eq!(router.current_history_index(), 1);
+        assert_eq!(router.peek_back(), Some(ExampleRoutes::Login));
+        assert_eq!(
+            router.peek_forward(),
+            Some(ExampleRoutes::Dashboard(DashboardRoutes::Profile(3)))
+        );
+
+        // The page is opened on another url.
+        let router: Router<ExampleRoutes> = Router::new()
+            .with_history_storage(storage)
+            .with_history_backend(MemoryHistory::new());
+        router.init(ExampleRoutes::Stuff.to_url());
+        assert_eq!(router.current_history_index(), 2);
+        assert_eq!(router.peek_back(), Some(ExampleRoutes::Register));
+        assert_eq!(router.peek_forward(), None);
+    }
+
+    impl Guarded<bool, ()> for ExampleRoutes {
+        fn check_guard(&self, logged: &bool) -> GuardResult<Self, ()> {
+            match self {
+                Self::Dashboard(_) if !*logged => GuardResult::Redirect(Self::Login),
+                _ => GuardResult::Allow,
+            }
+        }
+    }
+
+    #[test]
+    fn test_guard_redirect_replaces_the_route() {
+        let history = MemoryHistory::with_url(ExampleRoutes::Home.to_url());
+        let router: Router<ExampleRoutes> = Router::new().with_history_backend(history.clone());
+        router.init(history.current_url());
+        let dashboard = ExampleRoutes::Dashboard(DashboardRoutes::Root);
+
+        // Seed pushes the url of the link, then the app initialises the route,
+        // which is the login the guard redirects to.
+        history.push(&dashboard.to_url());
+        router.confirm_navigation(history.current_url());
+        assert!(router.check_current_route::<_, ()>(&false));
+        assert_eq!(router.current_route(), ExampleRoutes::Login);
+        assert_eq!(
+            router.with_history(<[ExampleRoutes]>::to_vec),
+            [ExampleRoutes::Home, ExampleRoutes::Login]
+        );
+        assert_eq!(history.current_url(), ExampleRoutes::Login.to_url());
+        assert_eq!(history.len(), 2);
+        assert_eq!(
+            history.calls().last(),
+            Some(&HistoryCall::Replace(ExampleRoutes::Login.to_url()))
+        );
+
+        history.push(&dashboard.to_url());
+        router.confirm_navigation(history.current_url());
+        assert!(router.check_current_route::<_, ()>(&true));
+        assert_eq!(router.current_route(), dashboard);
+        assert_eq!(history.current_url(), dashboard.to_url());
+    }
+
+    mod looping {
+        use super::*;
+        use router_derive::RoutingModules;
+        use seed::prelude::{Node, Orders};
+
+        pub struct Model;
+        pub type Msg = ();
+
+        /// Routes whose guards redirect to each other.
+        #[derive(Debug, PartialEq, Clone, RoutingModules)]
+        pub enum LoopRoutes {
+            #[guard(check = to_pong)]
+            #[view(render = empty)]
+            Ping,
+            #[guard(check = to_ping)]
+            #[view(render = empty)]
+            Pong,
+            #[default_route]
+            #[view(render = empty)]
+            NotFound,
+        }
+
+        fn empty(_: &Model) -> Node<Msg> {
+            Node::Empty
+        }
+
+        fn to_pong(_: &Model) -> GuardResult<LoopRoutes, Msg> {
+            GuardResult::Redirect(LoopRoutes::Pong)
+        }
+
+        fn to_ping(_: &Model) -> GuardResult<LoopRoutes, Msg> {
+            GuardResult::Redirect(LoopRoutes::Ping)
+        }
+    }
+
+    #[test]
+    fn test_resolve_guards_stops_on_a_loop() {
+        use looping::{LoopRoutes, Model, Msg};
+        let (route, result) = resolve_guards::<_, _, Msg>(LoopRoutes::Ping, &Model);
+        assert_eq!(route, LoopRoutes::Ping);
+        assert!(matches!(result, GuardResult::Pending));
+
+        let (route, result) = resolve_guards::<_, _, Msg>(LoopRoutes::Pong, &Model);
+        assert_eq!(route, LoopRoutes::Pong);
+        assert!(matches!(result, GuardResult::Pending));
+    }
+
+    #[test]
+    fn test_guard_redirect_loop_ends_pending() {
+        use looping::{LoopRoutes, Model, Msg};
+        let history = MemoryHistory::with_url(LoopRoutes::Ping.to_url());
+        let router: Router<LoopRoutes> = Router::new().with_history_backend(history.clone());
+        router.init(history.current_url());
+
+        // The route is not initialised, and stays as it is.
+        assert!(!router.check_current_route::<Model, Msg>(&Model));
+        assert_eq!(router.current_route(), LoopRoutes::Ping);
+        assert_eq!(
+            router.with_history(<[LoopRoutes]>::to_vec),
+            [LoopRoutes::Ping]
+        );
+        assert_eq!(history.current_url(), LoopRoutes::Ping.to_url());
+        assert!(!history
+            .calls()
+            .iter()
+            .any(|call| matches!(call, HistoryCall::Push(_) | HistoryCall::Replace(_))));
+    }
+
+    #[test]
+    fn test_navigate_to_url_checks_the_guards() {
+        let history = MemoryHistory::with_url(ExampleRoutes::Home.to_url());
+        let router: Router<ExampleRoutes> = Router::new().with_history_backend(history.clone());
+        router.init(history.current_url());
+        let dashboard = ExampleRoutes::Dashboard(DashboardRoutes::Root);
+
+        // The url of the dashboard never gets in the history.
+        router.navigate_to_url(dashboard.to_url(), &false);
+        assert_eq!(router.current_route(), ExampleRoutes::Login);
+        assert_eq!(history.current_url(), ExampleRoutes::Login.to_url());
+        assert_eq!(
+            router.with_history(<[ExampleRoutes]>::to_vec),
+            [ExampleRoutes::Home, ExampleRoutes::Login]
+        );
+        assert!(!history
+            .calls()
+            .contains(&HistoryCall::Push(dashboard.to_url())));
+
+        router.navigate_to_url(dashboard.to_url(), &true);
+        assert_eq!(router.current_route(), dashboard);
+        assert_eq!(history.current_url(), dashboard.to_url());
+    }
+
+    mod waiting {
+        use super::*;
+        use router_derive::RoutingModules;
+        use seed::prelude::{Node, Orders};
+
+        /// The user is loaded while `logged` is `None`.
+        pub struct Model {
+            pub logged: Option<bool>,
+        }
+        pub type Msg = ();
+
+        #[derive(Debug, PartialEq, Clone, RoutingModules)]
+        pub enum WaitingRoutes {
+            #[guard(check = logged)]
+            #[view(render = empty)]
+            Private,
+            #[default_route]
+            #[view(render = empty)]
+            Home,
+        }
+
+        fn empty(_: &Model) -> Node<Msg> {
+            Node::Empty
+        }
+
+        fn logged(model: &Model) -> GuardResult<WaitingRoutes, Msg> {
+            match model.logged {
+                None => GuardResult::Pending,
+                Some(true) => GuardResult::Allow,
+                Some(false) => GuardResult::Deny(Node::Empty),
+            }
+        }
+    }
+
+    #[test]
+    fn test_navigate_to_url_denied() {
+        use waiting::{Model, Msg, WaitingRoutes};
+        let history = MemoryHistory::with_url(WaitingRoutes::Home.to_url());
+        let router: Router<WaitingRoutes> = Router::new().with_history_backend(history.clone());
+        router.init(history.current_url());
+
+        let model = Model {
+            logged: Some(false),
+        };
+        router.navigate_to_url::<_, Msg>(WaitingRoutes::Private.to_url(), &model);
+        assert_eq!(router.current_route(), WaitingRoutes::Home);
+        assert_eq!(history.current_url(), WaitingRoutes::Home.to_url());
+        assert_eq!(history.len(), 1);
+    }
+
+    #[test]
+    fn test_pending_route_is_initialised_once_allowed() {
+        use waiting::{Model, Msg, WaitingRoutes};
+        let history = MemoryHistory::with_url(WaitingRoutes::Private.to_url());
+        let router: Router<WaitingRoutes> = Router::new().with_history_backend(history.clone());
+        router.init(history.current_url());
+        let mut model = Model { logged: None };
+
+        assert!(!router.check_current_route::<_, Msg>(&model));
+        // The user is loaded, the app checks the route again.
+        model.logged = Some(true);
+        assert!(router.check_current_route::<_, Msg>(&model));
+        // Nothing changed, the route is not initialised twice.
+        assert!(!router.check_current_route::<_, Msg>(&model));
+        assert_eq!(router.current_route(), WaitingRoutes::Private);
+
+        // Going to the route again initialises it again.
+        router.navigate_to_new(WaitingRoutes::Home);
+        router.back();
+        assert!(router.check_current_route::<_, Msg>(&model));
+        // The user logs out then in again.
+        model.logged = Some(false);
+        assert!(!router.check_current_route::<_, Msg>(&model));
+        model.logged = Some(true);
+        assert!(router.check_current_route::<_, Msg>(&model));
+    }
+
+    #[test]
+    fn test_memory_storage() {
+        let storage = MemoryStorage::new();
+        assert_eq!(storage.get_item("key"), None);
+        storage.set_item("key", "first");
+        storage.clone().set_item("key", "second");
+        assert_eq!(storage.get_item("key"), Some("second".to_string()));
+    }
+
+    #[test]
+    fn test_scroll_restoration() {
+        let history = MemoryHistory::new();
+        let router: Router<ScrollRoutes> = Router::new().with_history_backend(history.clone());
+        let scrolls = || {
+            history
+                .calls()
+                .into_iter()
+                .filter_map(|call| match call {
+                    HistoryCall::Scroll(scroll) => Some(scroll),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        let article = ScrollRoutes::Article {
+            id: 1,
+            fragment: Some("comments".to_string()),
+        };
+
+        // New navigations scroll to the top or to the fragment.
+        router.navigate_to_new(ScrollRoutes::Articles);
+        history.set_scroll_position(0., 800.);
+        router.navigate_to_new(article.clone());
+        assert_eq!(
+            scrolls(),
+            vec![
+                Scroll::Position(0., 0.),
+                Scroll::Fragment("comments".to_string())
+            ]
+        );
+
+        // Going back and forward restores the position the route was left at.
+        history.set_scroll_position(0., 300.);
+        router.back();
+        assert_eq!(history.scroll_position(), (0., 800.));
+        router.forward();
+        assert_eq!(history.scroll_position(), (0., 300.));
+
+        // The chat does not scroll, but the article is restored after it.
+        router.navigate_to_new(ScrollRoutes::Chat);
+        history.set_scroll_position(0., 50.);
+        router.back();
+        router.forward();
+        assert_eq!(scrolls().len(), 5);
+        assert_eq!(scrolls().last(), Some(&Scroll::Position(0., 300.)));
+        assert!(router.is_current_route(&ScrollRoutes::Chat));
+        assert!(!ScrollRoutes::Chat.restores_scroll());
+        assert_eq!(article.fragment(), Some("comments"));
+    }
     //
-    #[wasm_bindgen_test]
+    #[test]
     fn test_confirm_navigation() {
         let router: Router<ExampleRoutes> = Router::new();
         // When triggering from RequestedUrl
@@ -884,7 +2011,7 @@ mod test {
 
         // When navigating to wrong url, should go to default ( aka not found in this
         // example )
-        let url: Url = "/blabla/wrong_url".parse().unwrap();
+        let url = Url::new().set_path(["blabla", "wrong_url"]);
         router.confirm_navigation(url);
         {
             let router_data = router.data.borrow();
@@ -1,4 +1,5 @@
-use std::str::FromStr;
+use crate::{decode_path_segment, encode_path_segment};
+use std::{fmt, str::FromStr};
 
 /// Convert an enumeration variant to a string. Used as base for a route to be
 /// converted to `Url`.
@@ -10,7 +11,7 @@ pub trait AsPath {
 impl<T: ToString> AsPath for T {
     #[must_use]
     fn as_path(self) -> String {
-        format!("/{}", self.to_string())
+        format!("/{}", encode_path_segment(&self.to_string()))
     }
 }
 
@@ -73,28 +74,154 @@ pub trait ParsePath: AsPath + Sized {
     /// Implementation is provided for all types implementing `FromStr`,
     /// `ToString` and `AsPath`
     ///
-    /// The provided implementation trims all leading `'/'` characters, before
-    /// running the std parse
+    /// The provided implementation trims all leading `'/'` characters and
+    /// percent-decodes the string, before running the std parse
     ///
     /// # Errors
     ///
     /// Will return Err if it's not possible to parse this string slice into the
     /// desired type.
     fn parse_path(route: &str) -> Result<Self, ParseError>;
+
+    /// Parse the path of a nested route, once the path of its parent matched.
+    ///
+    /// The provided implementation is `parse_path`. Enums deriving `ParseUrl`
+    /// with a `#[default_route]` variant go to it instead of failing, so
+    /// `/dashboard/unknown` becomes `Dashboard(dashboard::Routes::NotFound)`.
+    ///
+    /// # Errors
+    ///
+    /// Will return Err if it's not possible to parse this string slice into the
+    /// desired type and there is no default route.
+    fn parse_children_path(path: &str) -> Result<Self, ParseError> {
+        Self::parse_path(path)
+    }
 }
 
 /// Error enum for parsing from string to `Route` with extra information.
 #[derive(Debug)]
 pub enum ParseError {
+    #[deprecated(note = "a segment which cannot be parsed is `ParseError::InvalidSegment`")]
     FromStr,
     NoMatch,
     By(String, Box<ParseError>),
     RemainingSegments,
+    /// A segment of the path is missing, such as the `id` in `/profile`.
+    MissingSegment(String),
+    /// A segment of the path cannot be decoded or parsed into the type of its
+    /// field.
+    InvalidSegment {
+        segment: String,
+        reason: String,
+    },
+    /// The query string cannot be parsed into the type of the `query` field.
+    InvalidQuery {
+        query: String,
+        reason: String,
+    },
+}
+
+#[allow(deprecated)]
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::FromStr => write!(f, "the string cannot be parsed"),
+            ParseError::NoMatch => write!(f, "no route matches the url"),
+            ParseError::By(route, err) => write!(f, "{}: {}", route, err),
+            ParseError::RemainingSegments => write!(f, "the url has remaining segments"),
+            ParseError::MissingSegment(segment) => write!(f, "the {} segment is missing", segment),
+            ParseError::InvalidSegment { segment, reason } => {
+                write!(f, "invalid segment '{}': {}", segment, reason)
+            }
+            ParseError::InvalidQuery { query, reason } => {
+                write!(f, "invalid query '{}': {}", query, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl ParseError {
+    /// The segment cannot be parsed into `T`.
+    pub fn invalid_segment<T>(segment: &str) -> Self {
+        ParseError::InvalidSegment {
+            segment: segment.to_string(),
+            reason: format!("cannot be parsed into {}", std::any::type_name::<T>()),
+        }
+    }
+
+    /// When no route matches, say which segment of the path did not match.
+    #[must_use]
+    pub fn with_unmatched_segment(self, path: &str) -> Self {
+        match self {
+            ParseError::NoMatch => ParseError::InvalidSegment {
+                segment: path
+                    .split(['/', '?', '#'])
+                    .next()
+                    .unwrap_or_default()
+                    .to_string(),
+                reason: "does not match any route".to_string(),
+            },
+            err => err,
+        }
+    }
+}
+
+/// The field capturing the remaining segments of the url with a `*` wildcard,
+/// such as `rest` in `#[as_path = "files/*"] Files { rest: Vec<String> }`.
+///
+/// Implemented for `Vec<String>` holding each segment and for `String` holding
+/// the segments joined with `'/'`.
+pub trait PathRest: Sized {
+    /// Build the field from the remaining path such as `docs/intro.md`.
+    ///
+    /// # Errors
+    ///
+    /// Will return Err if a segment cannot be percent-decoded.
+    fn parse_rest(path: &str) -> Result<Self, ParseError>;
+
+    /// The remaining path with its segments percent-encoded, without leading
+    /// `'/'`.
+    #[must_use]
+    fn as_rest(&self) -> String;
+}
+
+impl PathRest for Vec<String> {
+    fn parse_rest(path: &str) -> Result<Self, ParseError> {
+        path.split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(decode_path_segment)
+            .collect()
+    }
+
+    fn as_rest(&self) -> String {
+        self.iter()
+            .map(|segment| encode_path_segment(segment))
+            .collect::<Vec<String>>()
+            .join("/")
+    }
 }
+
+impl PathRest for String {
+    fn parse_rest(path: &str) -> Result<Self, ParseError> {
+        Vec::<String>::parse_rest(path).map(|segments| segments.join("/"))
+    }
+
+    fn as_rest(&self) -> String {
+        self.split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(encode_path_segment)
+            .collect::<Vec<String>>()
+            .join("/")
+    }
+}
+
 impl<T: FromStr + ToString + AsPath> ParsePath for T {
     fn parse_path(path: &str) -> Result<Self, ParseError> {
-        path.trim_start_matches('/')
+        let segment = path.trim_start_matches('/');
+        decode_path_segment(segment)?
             .parse::<T>()
-            .map_err(|_| ParseError::FromStr)
+            .map_err(|_| ParseError::invalid_segment::<T>(segment))
     }
 }
@@ -0,0 +1,11 @@
+/// Where the routes are read from and written to in the url.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum RoutingMode {
+    /// The route is the path of the url, such as `/dashboard/tasks`.
+    #[default]
+    Path,
+    /// The route is in the fragment of the url, such as `/#/dashboard/tasks`.
+    /// Static file hosts serve `index.html` for any route this way, since the
+    /// fragment is never sent to the server.
+    Hash,
+}
@@ -0,0 +1,62 @@
+use seed::prelude::web_sys;
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+/// A key-value storage the router saves its history in, so the history is
+/// restored after a reload of the page.
+/// `SessionStorage` keeps it for the browser tab and `MemoryStorage` runs
+/// without a browser, for native tests.
+pub trait HistoryStorage {
+    /// Get the value stored with the key, if any.
+    fn get_item(&self, key: &str) -> Option<String>;
+    /// Store the value with the key, replacing the previous one.
+    fn set_item(&self, key: &str, value: &str);
+}
+
+/// The `sessionStorage` of the web browser, kept as long as the tab is open.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SessionStorage;
+
+impl SessionStorage {
+    fn storage() -> Option<web_sys::Storage> {
+        web_sys::window()?.session_storage().ok()?
+    }
+}
+
+impl HistoryStorage for SessionStorage {
+    fn get_item(&self, key: &str) -> Option<String> {
+        Self::storage()?.get_item(key).ok()?
+    }
+
+    fn set_item(&self, key: &str, value: &str) {
+        if let Some(storage) = Self::storage() {
+            let _ = storage.set_item(key, value);
+        }
+    }
+}
+
+/// A storage kept in memory, to test the restoration of the history without
+/// a browser. Clones share the same items.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryStorage {
+    items: Rc<RefCell<HashMap<String, String>>>,
+}
+
+impl MemoryStorage {
+    /// Create an empty storage.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl HistoryStorage for MemoryStorage {
+    fn get_item(&self, key: &str) -> Option<String> {
+        self.items.borrow().get(key).cloned()
+    }
+
+    fn set_item(&self, key: &str, value: &str) {
+        self.items
+            .borrow_mut()
+            .insert(key.to_string(), value.to_string());
+    }
+}
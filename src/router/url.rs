@@ -1,5 +1,13 @@
-use crate::ParseError;
-use seed::{prelude::IndexMap, Url};
+use crate::{ParseError, RoutingMode};
+use percent_encoding::{
+    percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS, NON_ALPHANUMERIC,
+};
+use seed::{
+    prelude::{IndexMap, UrlSearch},
+    Url,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::str::FromStr;
 
 #[allow(clippy::module_name_repetitions)]
 #[allow(clippy::missing_errors_doc)]
@@ -10,142 +18,428 @@ pub trait ParseUrl {
     where
         Self: Sized;
     #[must_use]
-    /// Convert a route to a full url.
+    /// Convert a route to its url, from the root of the app.
+    /// `Router::url_for` gives the url with the `RoutingMode` and the base path
+    /// of the router, to use in the links of the app.
+    ///
+    /// Enums deriving `ParseUrl` give an empty url rather than panicking when
+    /// the path of the route cannot be decoded, which only a default route
+    /// filled by hand with such a path can have, and leave out a query which
+    /// cannot be serialized into key value pairs.
     fn to_url(&self) -> Url;
+    /// Get the default route filled with a url which does not match any
+    /// route, when the `#[default_route]` variant is `{ path: String }` or
+    /// `(Url)`.
+    fn default_route_for(_url: &Url) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        None
+    }
 }
 
-/// Convert the `IndexMap` from the Enum Variant to the string injected in the
-/// web browser.
-pub fn convert_to_string(query: &IndexMap<String, String>) -> String {
-    let mut query_string = "".to_string();
-    for (i, q) in query.iter().enumerate() {
-        query_string += format!("{}={}", q.0, q.1).as_str();
+/// Characters encoded in a path segment, everything but the unreserved
+/// characters of RFC 3986.
+const PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// Characters encoded in the fragment of a url, the fragment percent-encode
+/// set of the URL standard with `%` and `#`.
+const FRAGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'<')
+    .add(b'>')
+    .add(b'`')
+    .add(b'%')
+    .add(b'#');
+
+/// Percent-encode the id or a path parameter so it stays a single segment of
+/// the url, even with `/`, `?`, `&`, spaces or non-ASCII characters.
+pub fn encode_path_segment(segment: &str) -> String {
+    utf8_percent_encode(segment, PATH_SEGMENT).to_string()
+}
+
+/// Decode a percent-encoded path segment.
+/// # Errors
+/// Will return `ParseError::InvalidSegment` if the decoded segment is not
+/// valid UTF-8.
+pub fn decode_path_segment(segment: &str) -> Result<String, ParseError> {
+    percent_decode_str(segment)
+        .decode_utf8()
+        .map(std::borrow::Cow::into_owned)
+        .map_err(|_| ParseError::InvalidSegment {
+            segment: segment.to_string(),
+            reason: "is not valid UTF-8 once decoded".to_string(),
+        })
+}
+
+/// Rebuild the string of a `Url` the routes are parsed from, with the path
+/// parts and the query encoded again.
+///
+/// `Url` decodes its components, so `Url::to_string` would make a `/` in an id
+/// a new path segment.
+pub fn url_to_path(url: &Url) -> String {
+    let path: Vec<String> = url
+        .path()
+        .iter()
+        .map(|part| encode_path_segment(part))
+        .collect();
+    let query: Vec<(&String, &String)> = url
+        .search()
+        .iter()
+        .flat_map(|(key, values)| values.iter().map(move |value| (key, value)))
+        .collect();
+    let fragment = fragment_to_string(url.hash().map(String::as_str));
+    if query.is_empty() {
+        format!("/{}{}", path.join("/"), fragment)
+    } else {
+        format!(
+            "/{}?{}{}",
+            path.join("/"),
+            query_to_string(&query).unwrap_or_default(),
+            fragment
+        )
+    }
+}
+
+/// Build the `Url` of a url string such as `/dashboard/tasks?page=2#top`, the
+/// other way around of `url_to_path`.
+///
+/// `Url::from_str` relies on the browser, so the url is built here to work in
+/// native tests as well. A query key without value gets an empty value.
+/// # Errors
+/// Will return `ParseError::InvalidSegment` if a path segment or the fragment
+/// is not valid UTF-8 once decoded.
+pub fn path_to_url(path: &str) -> Result<Url, ParseError> {
+    let without_hash = without_fragment(path);
+    let (path_part, query) = without_hash.split_once('?').unwrap_or((without_hash, ""));
+    let segments = path_part
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(decode_path_segment)
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut search = UrlSearch::default();
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        search.push_value(decode_query_component(key), decode_query_component(value));
+    }
+    let url = Url::new().set_path(segments).set_search(search);
+    Ok(match extract_fragment(path)? {
+        Some(fragment) => url.set_hash(fragment),
+        None => url,
+    })
+}
+
+/// Get the url string of the route from the url, in its path without the base
+/// path or in its fragment depending on the `RoutingMode` of the router.
+/// # Errors
+/// Will return `ParseError::InvalidSegment` if the path of the url does not
+/// start with the base path in `RoutingMode::Path`.
+pub fn url_to_route_path(
+    url: &Url,
+    mode: RoutingMode,
+    base_path: &str,
+) -> Result<String, ParseError> {
+    let base_path = base_path.trim_matches('/');
+    match mode {
+        RoutingMode::Path => {
+            let path = url_to_path(url);
+            if base_path.is_empty() {
+                return Ok(path);
+            }
+            match strip_path_segment(path.trim_start_matches('/'), base_path) {
+                Some(rest) => Ok(format!("/{}", rest.trim_start_matches('/'))),
+                None => Err(ParseError::InvalidSegment {
+                    reason: format!("should start with the base path '/{}'", base_path),
+                    segment: path,
+                }),
+            }
+        }
+        RoutingMode::Hash => {
+            let hash = url.hash().map_or("", String::as_str);
+            Ok(format!("/{}", hash.trim_start_matches('/')))
+        }
+    }
+}
+
+/// Build the url of the url string of a route, in its path after the base path
+/// or in its fragment depending on the `RoutingMode` of the router.
+/// # Errors
+/// Will return `ParseError::InvalidSegment` if a segment of the url string is
+/// not valid UTF-8 once decoded in `RoutingMode::Path`.
+pub fn route_path_to_url(
+    route_path: &str,
+    mode: RoutingMode,
+    base_path: &str,
+) -> Result<Url, ParseError> {
+    let base_path = base_path.trim_matches('/');
+    match mode {
+        RoutingMode::Path if base_path.is_empty() => path_to_url(route_path),
+        RoutingMode::Path => path_to_url(&format!("/{}{}", base_path, route_path)),
+        RoutingMode::Hash => Ok(Url::new()
+            .set_path(base_path.split('/').filter(|part| !part.is_empty()))
+            .set_hash(route_path)),
+    }
+}
 
-        if i != query.len() - 1 {
-            query_string += "&".to_string().as_str();
+/// Convert the `fragment` field of the Enum Variant to the end of the url
+/// string, `#` and the percent-encoded fragment, or nothing without fragment.
+pub fn fragment_to_string(fragment: Option<&str>) -> String {
+    match fragment {
+        Some(fragment) if !fragment.is_empty() => {
+            format!("#{}", utf8_percent_encode(fragment, FRAGMENT))
         }
+        _ => String::new(),
     }
-    query_string
+}
+
+/// Extract the percent-decoded fragment after `#` from the url string for the
+/// `fragment` field of the Enum Variant.
+/// # Errors
+/// Will return `ParseError::InvalidSegment` if the fragment is not valid
+/// UTF-8 once decoded.
+pub fn extract_fragment(url_string: &str) -> Result<Option<String>, ParseError> {
+    match url_string.split_once('#') {
+        Some((_, fragment)) if !fragment.is_empty() => decode_path_segment(fragment).map(Some),
+        _ => Ok(None),
+    }
+}
+
+/// Remove the fragment from the url string, which ends the query.
+fn without_fragment(url_string: &str) -> &str {
+    url_string.split('#').next().unwrap_or_default()
+}
+
+/// Convert the `IndexMap` from the Enum Variant to the string injected in the
+/// web browser. Keys and values are percent-encoded.
+pub fn convert_to_string(query: &IndexMap<String, String>) -> String {
+    // A map of strings is always a flat list of key value pairs.
+    query_to_string(query).unwrap_or_default()
+}
+
+/// Convert the `query` field of the Enum Variant to the query string injected
+/// in the web browser. The field can be any type implementing `Serialize` into
+/// a flat list of key value pairs, such as an `IndexMap<String, String>` or a
+/// struct with optional fields, numbers and booleans.
+/// # Errors
+/// Will return `ParseError::InvalidQuery` if the query cannot be serialized
+/// into a query string, for example with a nested struct.
+pub fn query_to_string<T: Serialize>(query: &T) -> Result<String, ParseError> {
+    serde_urlencoded::to_string(query).map_err(|err| ParseError::InvalidQuery {
+        query: std::any::type_name::<T>().to_string(),
+        reason: err.to_string(),
+    })
+}
+
+/// Extract the query string from the url string and deserialize it into the
+/// type of the `query` field of the Enum Variant.
+/// # Errors
+/// Will return `ParseError::InvalidQuery` if the query cannot be deserialized
+/// into the type, for example when a required key is missing.
+pub fn extract_query<T: DeserializeOwned>(url_string: &str) -> Result<T, ParseError> {
+    let query = without_fragment(url_string)
+        .split_once('?')
+        .map_or("", |(_, query)| query);
+    serde_urlencoded::from_str(query).map_err(|err| ParseError::InvalidQuery {
+        query: query.to_string(),
+        reason: err.to_string(),
+    })
 }
 
 /// Extract from url string the id, the query parameter and the children route
 /// to rebuild the enum variant.
+/// # Errors
+/// Will return the `ParseError` of the first payload that cannot be extracted.
+#[allow(clippy::type_complexity)]
 pub fn extract_url_payload(
     url_string: String,
     with_id_param: bool,
     with_query_parameters: bool,
     with_children: bool,
-) -> (
-    Option<String>,
-    Option<IndexMap<String, String>>,
-    Option<String>,
-) {
+) -> Result<
+    (
+        Option<String>,
+        Option<IndexMap<String, String>>,
+        Option<String>,
+    ),
+    ParseError,
+> {
     let param_id = if with_id_param {
-        Some(extract_id_parameter(&url_string))
+        Some(extract_id_parameter(&url_string)?)
     } else {
         None
     };
 
     let query_parameters = if with_query_parameters {
-        Some(extract_query_params(&url_string))
+        Some(extract_query_params(&url_string)?)
     } else {
         None
     };
 
     let children_path = if with_children {
-        Some(extract_children_string(url_string, param_id.clone()))
+        Some(extract_children_string(url_string, param_id.clone())?)
     } else {
         None
     };
 
-    (param_id, query_parameters, children_path)
+    Ok((param_id, query_parameters, children_path))
 }
 /// Extract the id parameter from the url string for the Enum Variant.
-/// # Panics
-/// The function will panic if the root path start withs special character `/`.
-pub fn extract_id_parameter(url_string: &str) -> String {
+/// # Errors
+/// Will return `ParseError::InvalidSegment` if the url string does not start
+/// with `/`, as with `/profileextra`, and `ParseError::MissingSegment` if there
+/// is no id.
+pub fn extract_id_parameter(url_string: &str) -> Result<String, ParseError> {
     let mut single_paths = url_string.split('/');
 
-    let root = single_paths.next();
-
-    if root.is_some()
-        && !root
-            .expect("Should get the root path of the url")
-            .is_empty()
-    {
-        panic!("root path should be like '' because urls starts with / ");
+    match single_paths.next() {
+        Some(root) if !root.is_empty() => {
+            return Err(ParseError::InvalidSegment {
+                segment: root.to_string(),
+                reason: "should be separated from the path by '/'".to_string(),
+            })
+        }
+        _ => {}
     }
-    // make error if root is not empty
-    let mut param_id = single_paths
+    let param_id = single_paths
         .next()
-        .map(std::string::ToString::to_string)
-        .expect("Should have param id");
+        .ok_or_else(|| ParseError::MissingSegment("id".to_string()))?;
 
-    if param_id.contains('?') {
-        param_id = param_id
-            .split('?')
-            .next()
-            .map(std::string::ToString::to_string)
-            .expect("We should have a id parameter but got empty string")
+    Ok(param_id
+        .split(['?', '#'])
+        .next()
+        .unwrap_or_default()
+        .to_string())
+}
+
+/// Parse the id parameter extracted from the url string into the type of the
+/// `id` field of the Enum Variant, such as `String`, `u32` or `Uuid`. The id
+/// parameter is percent-decoded first.
+/// # Errors
+/// Will return `ParseError::InvalidSegment` if the id parameter cannot be
+/// decoded or parsed into the type.
+pub fn parse_id_parameter<T: FromStr>(param_id: &str) -> Result<T, ParseError> {
+    decode_path_segment(param_id)?
+        .parse::<T>()
+        .map_err(|_| ParseError::invalid_segment::<T>(param_id))
+}
+
+/// Strip the path of a variant from the start of the url string only if it
+/// ends on a segment boundary, so `admin` matches `admin/1` and `admin?user=arn`
+/// but not `administrator`.
+pub fn strip_path_segment<'a>(url_string: &'a str, path: &str) -> Option<&'a str> {
+    url_string
+        .strip_prefix(path)
+        .filter(|rest| rest.is_empty() || rest.starts_with(['/', '?', '#']))
+}
+
+/// Check that nothing but a query or a fragment is left in the url string once
+/// a route matched.
+/// # Errors
+/// Will return `ParseError::RemainingSegments` if there are segments left, as
+/// with `/home/anything/else` for a `Home` route.
+pub fn check_remaining_segments(url_string: &str) -> Result<(), ParseError> {
+    match url_string.split(['?', '#']).next().unwrap_or_default() {
+        "" | "/" => Ok(()),
+        _ => Err(ParseError::RemainingSegments),
     }
-    param_id
 }
+
+/// Match the start of the url string against a path with segments from
+/// `#[as_path]` such as `orgs/:org_id/projects/:project_id`.
+///
+/// Return the values of the `:name` segments in the order of the path and the
+/// rest of the url string, or `None` if the url does not match the path.
+/// A last `*` segment captures all the remaining segments, possibly none, as
+/// one value.
+pub fn extract_path_parameters<'a>(
+    url_string: &'a str,
+    path: &str,
+) -> Option<(Vec<String>, &'a str)> {
+    let mut rest = url_string;
+    let mut params = Vec::new();
+    for (i, segment) in path.split('/').enumerate() {
+        if segment == "*" {
+            let rest = rest.strip_prefix('/').unwrap_or(rest);
+            let end = rest.find(['?', '#']).unwrap_or(rest.len());
+            let (value, remaining) = rest.split_at(end);
+            params.push(value.to_string());
+            return Some((params, remaining));
+        }
+        if i > 0 {
+            rest = rest.strip_prefix('/')?;
+        }
+        let end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+        let (value, remaining) = rest.split_at(end);
+        if segment.starts_with(':') {
+            if value.is_empty() {
+                return None;
+            }
+            params.push(value.to_string());
+        } else if value != segment {
+            return None;
+        }
+        rest = remaining;
+    }
+    Some((params, rest))
+}
+
 /// Extract the children route string from url string and conserves the id
 /// parameter if any for the Enum Variant.
-pub fn extract_children_string(url_string: String, param_id: Option<String>) -> String {
-    let full_query = url_string;
-    let children_path: Option<String>;
-
-    if param_id.is_some() {
-        children_path = full_query
+/// # Errors
+/// Will return `ParseError::InvalidSegment` if the url string does not start
+/// with the id parameter.
+pub fn extract_children_string(
+    url_string: String,
+    param_id: Option<String>,
+) -> Result<String, ParseError> {
+    match param_id {
+        Some(param_id) => url_string
             .trim_start_matches('/')
-            .to_string()
-            .strip_prefix(&param_id.expect("should have id parameter"))
-            .map(std::string::ToString::to_string);
-    } else {
-        children_path = Some(full_query)
+            .strip_prefix(&param_id)
+            .map(std::string::ToString::to_string)
+            .ok_or(ParseError::InvalidSegment {
+                segment: url_string,
+                reason: format!("should start with the id '{}'", param_id),
+            }),
+        None => Ok(url_string),
     }
-
-    children_path.expect("We should have a children path")
 }
+/// Decode a key or a value of a query string, where spaces can be written
+/// `+`.
+fn decode_query_component(component: &str) -> String {
+    percent_decode_str(&component.replace('+', " "))
+        .decode_utf8_lossy()
+        .into_owned()
+}
+
 /// Extract the query parameters from the url string and return an `IndexMap`
-/// for the Enum Variant.
-/// # Panics
-/// The function will panic a key has no value.
-/// # Warns
-/// A warning will be emitted if the query parameter string only contains `?`
-/// with no query. Theses choices are opinionated for now.
-pub fn extract_query_params(url_string: &str) -> IndexMap<String, String> {
+/// for the Enum Variant. Keys and values are percent-decoded, and a query
+/// string made of a single `?` gives an empty map.
+/// # Errors
+/// Will return `ParseError::InvalidQuery` if a key has no value, as with
+/// `/admin?flag`.
+pub fn extract_query_params(url_string: &str) -> Result<IndexMap<String, String>, ParseError> {
     let mut query: IndexMap<String, String> = IndexMap::new();
-    let url_parts: Vec<&str> = url_string.split('?').collect();
-    let mut parts_iter = url_parts.iter();
-
-    let _ = parts_iter.next();
-    if let Some(sub_string) = parts_iter.next() {
-        if sub_string.is_empty() {
-            eprintln!(
-                "query parameter is empty because query string only contains
-            '?'"
-            );
-        } else {
+    // A query can contain a `?`, only the first one starts it.
+    if let Some((_, sub_string)) = without_fragment(url_string).split_once('?') {
+        if !sub_string.is_empty() {
             let key_value: Vec<&str> = sub_string.split('&').collect();
 
             for pair in key_value {
-                let mut sub = pair.split('=');
-                let key = sub.next().unwrap_or_else(|| {
-                    panic!(
-                        "we should have a key for the parameter key but got {}",
-                        url_string
-                    )
-                });
-                let value = sub.next().unwrap_or_else(|| {
-                    panic!("we should have a value for the key but got {}", url_string)
-                });
-                query.insert(key.to_string(), value.to_string());
+                let (key, value) = pair.split_once('=').ok_or(ParseError::InvalidQuery {
+                    query: sub_string.to_string(),
+                    reason: format!("the key '{}' has no value", pair),
+                })?;
+                query.insert(decode_query_component(key), decode_query_component(value));
             }
         }
     }
-    query
+    Ok(query)
 }
 #[cfg(test)]
 mod test {
@@ -179,22 +473,161 @@ mod test {
     fn test_extract_id_param() {
         let url_string = "/12/stuff?user=arn&role=programmer";
 
-        let id_param = extract_id_parameter(&url_string.to_string());
+        let id_param = extract_id_parameter(&url_string.to_string()).unwrap();
 
         assert_eq!(id_param, "12");
 
         let url_string = "/12?user=arn&role=programmer";
 
-        let id_param = extract_id_parameter(&url_string.to_string());
+        let id_param = extract_id_parameter(&url_string.to_string()).unwrap();
 
         assert_eq!(id_param, "12");
     }
 
+    #[test]
+    fn test_extract_id_param_errors() {
+        assert!(matches!(
+            extract_id_parameter(""),
+            Err(ParseError::MissingSegment(_))
+        ));
+        assert!(matches!(
+            extract_id_parameter("extra/12"),
+            Err(ParseError::InvalidSegment { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_id_param() {
+        let id: String = parse_id_parameter("12").unwrap();
+        assert_eq!(id, "12");
+
+        let id: u32 = parse_id_parameter("12").unwrap();
+        assert_eq!(id, 12);
+
+        let id: Result<u32, ParseError> = parse_id_parameter("twelve");
+        assert!(matches!(id, Err(ParseError::InvalidSegment { .. })));
+    }
+
+    #[test]
+    fn test_fragment() {
+        assert_eq!(
+            extract_fragment("/docs?page=2#install").unwrap(),
+            Some("install".to_string())
+        );
+        assert_eq!(
+            extract_fragment("/docs#step%201%20%2F%202").unwrap(),
+            Some("step 1 / 2".to_string())
+        );
+        assert_eq!(extract_fragment("/docs#").unwrap(), None);
+        assert_eq!(extract_fragment("/docs?page=2").unwrap(), None);
+
+        assert_eq!(fragment_to_string(Some("install")), "#install");
+        assert_eq!(fragment_to_string(Some("100% #1")), "#100%25%20%231");
+        assert_eq!(fragment_to_string(Some("")), "");
+        assert_eq!(fragment_to_string(None), "");
+        for fragment in &["step 1/2", "100% #1", "日本語"] {
+            let url_string = format!("/docs{}", fragment_to_string(Some(fragment)));
+            assert_eq!(
+                extract_fragment(&url_string).unwrap().as_deref(),
+                Some(*fragment)
+            );
+        }
+    }
+
+    #[test]
+    fn test_path_to_url() {
+        let url = path_to_url("/docs/a%2Fb?page=2&flag#top").unwrap();
+        assert_eq!(url.path(), &["docs".to_string(), "a/b".to_string()]);
+        assert_eq!(url.search().get("page"), Some(&vec!["2".to_string()]));
+        assert_eq!(url.search().get("flag"), Some(&vec![String::new()]));
+        assert_eq!(url.hash().map(String::as_str), Some("top"));
+        assert_eq!(url_to_path(&url), "/docs/a%2Fb?flag=&page=2#top");
+        assert!(path_to_url("/docs/%FF").is_err());
+    }
+
+    #[test]
+    fn test_route_path_to_url() {
+        assert_eq!(
+            route_path_to_url("/login", RoutingMode::Path, "/apps/crm/").unwrap(),
+            Url::new().set_path(["apps", "crm", "login"])
+        );
+        assert_eq!(
+            route_path_to_url("/login", RoutingMode::Hash, "apps").unwrap(),
+            Url::new().set_path(["apps"]).set_hash("/login")
+        );
+        assert!(matches!(
+            route_path_to_url("/%FF", RoutingMode::Path, ""),
+            Err(ParseError::InvalidSegment { .. })
+        ));
+    }
+
+    #[test]
+    fn test_strip_path_segment() {
+        assert_eq!(strip_path_segment("admin", "admin"), Some(""));
+        assert_eq!(strip_path_segment("admin/1", "admin"), Some("/1"));
+        assert_eq!(
+            strip_path_segment("admin?user=arn", "admin"),
+            Some("?user=arn")
+        );
+        assert_eq!(strip_path_segment("admin#users", "admin"), Some("#users"));
+        assert_eq!(strip_path_segment("administrator", "admin"), None);
+        assert_eq!(strip_path_segment("home", "admin"), None);
+    }
+
+    #[test]
+    fn test_check_remaining_segments() {
+        assert!(check_remaining_segments("").is_ok());
+        assert!(check_remaining_segments("/").is_ok());
+        assert!(check_remaining_segments("?user=arn/role").is_ok());
+        assert!(check_remaining_segments("#section/2").is_ok());
+        assert!(matches!(
+            check_remaining_segments("/anything/else"),
+            Err(ParseError::RemainingSegments)
+        ));
+    }
+
+    #[test]
+    fn test_extract_path_parameters() {
+        let (params, rest) = extract_path_parameters(
+            "orgs/seed/projects/42/12?user=arn",
+            "orgs/:org_id/projects/:project_id",
+        )
+        .unwrap();
+        assert_eq!(params, vec!["seed".to_string(), "42".to_string()]);
+        assert_eq!(rest, "/12?user=arn");
+
+        let (params, rest) = extract_path_parameters("api/v1", "api/v1").unwrap();
+        assert!(params.is_empty());
+        assert_eq!(rest, "");
+
+        assert!(
+            extract_path_parameters("orgs/seed", "orgs/:org_id/projects/:project_id").is_none()
+        );
+        assert!(
+            extract_path_parameters("orgs//projects/42", "orgs/:org_id/projects/:project_id")
+                .is_none()
+        );
+        assert!(extract_path_parameters(
+            "users/seed/projects/42",
+            "orgs/:org_id/projects/:project_id"
+        )
+        .is_none());
+
+        let (params, rest) =
+            extract_path_parameters("files/docs/intro.md?raw=true", "files/*").unwrap();
+        assert_eq!(params, vec!["docs/intro.md".to_string()]);
+        assert_eq!(rest, "?raw=true");
+        let (params, rest) = extract_path_parameters("files", "files/*").unwrap();
+        assert_eq!(params, vec![String::new()]);
+        assert_eq!(rest, "");
+        assert!(extract_path_parameters("filesystem/docs", "files/*").is_none());
+    }
+
     #[test]
     fn test_extract_query_params() {
         let url_string = "/12/stuff?user=arn&role=programmer";
 
-        let params = extract_query_params(&url_string.to_string());
+        let params = extract_query_params(&url_string.to_string()).unwrap();
         let mut query_to_compare: IndexMap<String, String> = IndexMap::new();
 
         query_to_compare.insert("user".to_string(), "arn".to_string());
@@ -203,33 +636,177 @@ mod test {
 
         let url_string = "/12/stuff";
 
-        let params = extract_query_params(&url_string.to_string());
+        let params = extract_query_params(&url_string.to_string()).unwrap();
         let query_to_compare: IndexMap<String, String> = IndexMap::new();
 
         assert_eq!(params, query_to_compare);
 
         let url_string = "/12/stuff?";
 
-        let params = extract_query_params(&url_string.to_string());
+        let params = extract_query_params(&url_string.to_string()).unwrap();
         let query_to_compare: IndexMap<String, String> = IndexMap::new();
 
         assert_eq!(params, query_to_compare);
+
+        let url_string = "/search?q=a?b&page=2";
+
+        let params = extract_query_params(url_string).unwrap();
+        let mut query_to_compare: IndexMap<String, String> = IndexMap::new();
+
+        query_to_compare.insert("q".to_string(), "a?b".to_string());
+        query_to_compare.insert("page".to_string(), "2".to_string());
+        assert_eq!(params, query_to_compare);
     }
+    #[derive(Debug, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+    #[serde(default)]
+    struct Filters {
+        page: u32,
+        archived: bool,
+        search: Option<String>,
+    }
+
+    #[derive(serde::Serialize)]
+    struct NestedFilters {
+        filters: Filters,
+    }
+
+    #[test]
+    fn test_extract_query() {
+        let filters: Filters = extract_query("/12/stuff?page=3&archived=true").unwrap();
+        assert_eq!(
+            filters,
+            Filters {
+                page: 3,
+                archived: true,
+                search: None
+            }
+        );
+
+        let filters: Filters = extract_query("/12/stuff").unwrap();
+        assert_eq!(filters, Filters::default());
+
+        let filters: Filters = extract_query("/12/stuff?page=3#page=4").unwrap();
+        assert_eq!(filters.page, 3);
+
+        let filters: Result<Filters, ParseError> = extract_query("/12/stuff?page=last");
+        assert!(matches!(filters, Err(ParseError::InvalidQuery { .. })));
+
+        let query: IndexMap<String, String> =
+            extract_query("/12/stuff?user=arn&role=programmer").unwrap();
+        let mut query_to_compare: IndexMap<String, String> = IndexMap::new();
+        query_to_compare.insert("user".to_string(), "arn".to_string());
+        query_to_compare.insert("role".to_string(), "programmer".to_string());
+        assert_eq!(query, query_to_compare);
+    }
+
+    #[test]
+    fn test_query_to_string() {
+        let filters = Filters {
+            page: 3,
+            archived: false,
+            search: Some("seed routing".to_string()),
+        };
+        assert_eq!(
+            query_to_string(&filters).unwrap(),
+            "page=3&archived=false&search=seed+routing"
+        );
+
+        let mut query: IndexMap<String, String> = IndexMap::new();
+        query.insert("user".to_string(), "arn".to_string());
+        query.insert("role".to_string(), "programmer".to_string());
+        assert_eq!(query_to_string(&query).unwrap(), "user=arn&role=programmer");
+        assert!(matches!(
+            query_to_string(&NestedFilters { filters }),
+            Err(ParseError::InvalidQuery { .. })
+        ));
+    }
+
+    #[test]
+    fn test_encode_path_segment() {
+        let segments = [
+            "2019/units/Camor",
+            "a&b=c?d#e",
+            "hello world",
+            "100%",
+            "Günter",
+            "日本語",
+            "plain-id_1.2~3",
+        ];
+        for segment in &segments {
+            let encoded = encode_path_segment(segment);
+            assert!(!encoded.contains(&['/', '?', '&', '#', ' '][..]));
+            assert_eq!(decode_path_segment(&encoded).unwrap(), *segment);
+        }
+        assert_eq!(encode_path_segment("2019/units"), "2019%2Funits");
+        assert_eq!(encode_path_segment("plain-id_1.2~3"), "plain-id_1.2~3");
+        assert_eq!(encode_path_segment("Günter"), "G%C3%BCnter");
+
+        let id: String = parse_id_parameter("hello%20w%C3%B6rld").unwrap();
+        assert_eq!(id, "hello wörld");
+        assert!(decode_path_segment("%FF").is_err());
+    }
+
+    #[test]
+    fn test_url_to_path() {
+        let url = Url::new()
+            .set_path(vec!["profile", "Günter/Grass"])
+            .set_search(seed::prelude::UrlSearch::new(vec![("name", vec!["a&b c"])]));
+        assert_eq!(
+            url_to_path(&url),
+            "/profile/G%C3%BCnter%2FGrass?name=a%26b+c"
+        );
+
+        assert_eq!(url_to_path(&Url::new()), "/");
+    }
+
+    #[test]
+    fn test_query_round_trip() {
+        let mut query: IndexMap<String, String> = IndexMap::new();
+        query.insert("search".to_string(), "a&b=c?d/e#f".to_string());
+        query.insert("name".to_string(), "Günter Grass".to_string());
+        query.insert("1+1".to_string(), "2%".to_string());
+
+        let query_string = convert_to_string(&query);
+        assert!(!query_string.contains(&['/', '?', '#', ' '][..]));
+        assert_eq!(query_string.matches('&').count(), 2);
+
+        let url_string = "/search?".to_string() + &query_string;
+        assert_eq!(extract_query_params(&url_string).unwrap(), query);
+        let decoded: IndexMap<String, String> = extract_query(&url_string).unwrap();
+        assert_eq!(decoded, query);
+    }
+
+    #[test]
+    fn test_extract_query_params_without_value() {
+        let params = extract_query_params("/admin?flag");
+        assert!(matches!(params, Err(ParseError::InvalidQuery { .. })));
+
+        let params = extract_query_params("/admin?user=arn&flag");
+        match params {
+            Err(err) => assert_eq!(
+                err.to_string(),
+                "invalid query 'user=arn&flag': the key 'flag' has no value"
+            ),
+            Ok(_) => panic!("a key without value should not be parsed"),
+        }
+    }
+
     #[test]
     fn test_extract_children() {
         let url_string = "/12/stuff?user=arn&role=programmer";
-        let children = extract_children_string(url_string.to_string(), Some("12".to_string()));
+        let children =
+            extract_children_string(url_string.to_string(), Some("12".to_string())).unwrap();
         assert_eq!(children, "/stuff?user=arn&role=programmer");
 
         let url_string = "/12/stuff?user=arn&role=programmer";
-        let children = extract_children_string(url_string.to_string(), None);
+        let children = extract_children_string(url_string.to_string(), None).unwrap();
         assert_eq!(children, "/12/stuff?user=arn&role=programmer");
     }
     #[test]
     fn test_string_to_index_map() {
         let string = "/task?user=arn&role=programmer";
 
-        let query = extract_url_payload(string.to_string(), false, true, false);
+        let query = extract_url_payload(string.to_string(), false, true, false).unwrap();
 
         let mut query_to_compare: IndexMap<String, String> = IndexMap::new();
 
@@ -246,7 +823,7 @@ mod test {
         let task: UserTask2 = string
             .trim_start_matches('/')
             .strip_prefix("task")
-            .map(|rest| extract_url_payload(rest.to_string(), true, true, false))
+            .map(|rest| extract_url_payload(rest.to_string(), true, true, false).unwrap())
             .map(|(id, query, _)| (id.unwrap(), query.unwrap()))
             .map(|(id, query)| UserTask2 { id, query })
             .unwrap();
@@ -260,7 +837,7 @@ mod test {
         assert_eq!(task.query, query_to_compare);
 
         let string = "?user=arn&role=programmer";
-        let query = extract_url_payload(string.to_string(), false, true, true);
+        let query = extract_url_payload(string.to_string(), false, true, true).unwrap();
         let mut query_to_compare: IndexMap<String, String> = IndexMap::new();
         query_to_compare.insert("user".to_string(), "arn".to_string());
         query_to_compare.insert("role".to_string(), "programmer".to_string());
@@ -274,7 +851,7 @@ mod test {
         let task: UserTask = string
             .trim_start_matches('/')
             .strip_prefix("task")
-            .map(|rest| extract_url_payload(rest.to_string(), true, true, true))
+            .map(|rest| extract_url_payload(rest.to_string(), true, true, true).unwrap())
             .map(|(id, query, children)| (id.unwrap(), query.unwrap(), children.unwrap()))
             .map(|(id, query, children)| UserTask {
                 id,
@@ -299,7 +876,7 @@ mod test {
         let task: UserTask4 = string
             .trim_start_matches('/')
             .strip_prefix("task")
-            .map(|rest| extract_url_payload(rest.to_string(), true, false, true))
+            .map(|rest| extract_url_payload(rest.to_string(), true, false, true).unwrap())
             .map(|(id, query, children)| (id.unwrap(), query, children.unwrap()))
             .map(|(id, _, children)| UserTask4 { id, children })
             .unwrap();
@@ -314,7 +891,7 @@ mod test {
         let task: UserTask3 = string
             .trim_start_matches('/')
             .strip_prefix("task")
-            .map(|rest| extract_url_payload(rest.to_string(), false, true, true))
+            .map(|rest| extract_url_payload(rest.to_string(), false, true, true).unwrap())
             .map(|(id, query, children)| (id, query.unwrap(), children.unwrap()))
             .map(|(_, query, children)| UserTask3 { query, children })
             .unwrap();
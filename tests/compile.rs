@@ -0,0 +1,8 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+#[test]
+fn test_routes_compile() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/*-pass.rs");
+    t.compile_fail("tests/ui/*-fail.rs");
+}
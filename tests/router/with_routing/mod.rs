@@ -36,10 +36,17 @@ mod test {
     #[derive(Debug, PartialEq, Clone, RoutingModules)]
     #[modules_path = "pages"]
     pub enum Routes {
-        #[view = "=> login"]
+        #[view(render = login)]
         Login,
-        #[guard = "user => guard => forbidden"]
+        #[guard(model = user, check = guard, otherwise = forbidden)]
         Dashboard(dashboard::Routes),
+        #[guard(model = user, check = login_required)]
+        #[view(render = account)]
+        Account,
+        #[guard(model = user, check = login_required)]
+        #[guard(model = user, check = any(named_admin, named_owner))]
+        #[view(render = account)]
+        Reports,
         Admin {
             query: IndexMap<String, String>,
         },
@@ -49,10 +56,10 @@ mod test {
             children: other::Routes,
         },
         #[default_route]
-        #[view = "=> not_found"]
+        #[view(render = not_found)]
         NotFound,
         #[as_path = ""]
-        #[view = "=> home"]
+        #[view(render = home)]
         Home,
     }
 
@@ -85,7 +92,7 @@ mod test {
     fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
         match msg {
             Msg::UrlChanged(subs::UrlChanged(_url)) => {
-                router().current_route().init(model, orders);
+                router().init_current_route(model, orders);
             }
 
             Msg::GoBack => {
@@ -125,6 +132,25 @@ mod test {
             None
         }
     }
+    pub fn login_required(user: Option<&UserLogged>) -> GuardResult<Routes, Msg> {
+        match user {
+            Some(user_logged) if user_logged.name.is_empty() => GuardResult::Deny(forbidden(user)),
+            Some(_) => GuardResult::Allow,
+            None => GuardResult::Redirect(Routes::Login),
+        }
+    }
+    pub fn named_admin(user: Option<&UserLogged>) -> GuardResult<Routes, Msg> {
+        match user {
+            Some(user_logged) if user_logged.name == "admin" => GuardResult::Allow,
+            _ => GuardResult::Deny(forbidden(user)),
+        }
+    }
+    pub fn named_owner(user: Option<&UserLogged>) -> GuardResult<Routes, Msg> {
+        match user {
+            Some(user_logged) if user_logged.name == "owner" => GuardResult::Allow,
+            _ => GuardResult::Pending,
+        }
+    }
     // ------ ------
     //     View
     // ------ ------
@@ -138,6 +164,11 @@ mod test {
         div!["login"]
     }
 
+    /// A local view account
+    fn account(_: &Model) -> Node<Msg> {
+        div!["account"]
+    }
+
     /// A local view home
     fn home(_: &Model) -> Node<Msg> {
         div!["home"]
@@ -265,6 +296,121 @@ mod test {
         );
     }
 
+    #[wasm_bindgen_test]
+    fn test_router_view_and_guard_result() {
+        let my_router: Router<Routes> = router();
+        let model = |user: Option<UserLogged>| Model {
+            dashboard: dashboard::Model::default(),
+            admin: admin::Model::default(),
+            other: other::Model::default(),
+            user,
+        };
+
+        my_router.navigate_to_new(Routes::Account);
+        let current_view = my_router
+            .current_route()
+            .view(&model(Some(UserLogged {
+                name: "test_user".to_string(),
+            })))
+            .to_string();
+        assert_eq!(current_view, account(&model(None)).to_string());
+
+        let current_view = my_router
+            .current_route()
+            .view(&model(Some(UserLogged {
+                name: "".to_string(),
+            })))
+            .to_string();
+        assert_eq!(current_view, forbidden(None).to_string());
+
+        // The anonymous user is redirected to the login by
+        // `Router::init_current_route`, the view displays nothing until then.
+        let current_view = my_router.current_route().view(&model(None)).to_string();
+        assert_eq!(current_view, Node::<Msg>::Empty.to_string());
+        let (route, _) = resolve_guards::<Routes, Model, Msg>(Routes::Account, &model(None));
+        assert_eq!(route, Routes::Login);
+        assert_eq!(my_router.current_route(), Routes::Account);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_router_view_and_stacked_guards() {
+        let my_router: Router<Routes> = router();
+        let view_for = |name: Option<&str>| {
+            my_router.navigate_to_new(Routes::Reports);
+            my_router
+                .current_route()
+                .view(&Model {
+                    dashboard: dashboard::Model::default(),
+                    admin: admin::Model::default(),
+                    other: other::Model::default(),
+                    user: name.map(|name| UserLogged {
+                        name: name.to_string(),
+                    }),
+                })
+                .to_string()
+        };
+        let model = Model {
+            dashboard: dashboard::Model::default(),
+            admin: admin::Model::default(),
+            other: other::Model::default(),
+            user: None,
+        };
+
+        assert_eq!(view_for(Some("admin")), account(&model).to_string());
+        assert_eq!(view_for(Some("owner")), account(&model).to_string());
+        // Neither guard of `any` allows, the result of the first one wins.
+        assert_eq!(view_for(Some("test_user")), forbidden(None).to_string());
+        // The first guard redirects before the others are checked.
+        assert_eq!(view_for(None), Node::<Msg>::Empty.to_string());
+        let (route, _) = resolve_guards::<Routes, Model, Msg>(Routes::Reports, &model);
+        assert_eq!(route, Routes::Login);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_router_view_and_nested_guards() {
+        let my_router: Router<Routes> = router();
+        let model = |user: Option<&str>, stuff: &str| Model {
+            dashboard: dashboard::Model {
+                stuff: stuff.to_string(),
+            },
+            admin: admin::Model::default(),
+            other: other::Model::default(),
+            user: user.map(|name| UserLogged {
+                name: name.to_string(),
+            }),
+        };
+        let view = |model: &Model| my_router.current_route().view(model).to_string();
+
+        // The guard of the parent route applies to the nested routes.
+        my_router.navigate_to_new(Dashboard(dashboard::Routes::Private));
+        assert_eq!(view(&model(None, "")), forbidden(None).to_string());
+
+        // Then the guards of the nested routes get the model of their module.
+        assert_eq!(
+            view(&model(Some("test_user"), "")),
+            dashboard::not_initialised(&dashboard::Model::default()).to_string()
+        );
+        assert_eq!(
+            view(&model(Some("test_user"), "init_has_been_done")),
+            dashboard::settings(&dashboard::Model::default()).to_string()
+        );
+
+        // A redirect to nested routes goes to the parent route holding them.
+        my_router.navigate_to_new(Dashboard(dashboard::Routes::Reports));
+        let history_length = my_router.with_history(<[Routes]>::len);
+        let (route, _) = resolve_guards::<Routes, Model, Msg>(
+            my_router.current_route(),
+            &model(Some("test_user"), ""),
+        );
+        assert_eq!(route, Dashboard(dashboard::Routes::Settings));
+        my_router.navigate_replace(route);
+        assert_eq!(
+            view(&model(Some("test_user"), "")),
+            dashboard::settings(&dashboard::Model::default()).to_string()
+        );
+        assert_eq!(my_router.with_history(<[Routes]>::len), history_length);
+    }
+
     #[wasm_bindgen_test]
     fn test_router_navigation_and_page_init() {
         let mut model = Model {
@@ -293,8 +439,20 @@ mod test {
 
         assert_eq!(&model.dashboard.stuff, "");
         let dashboard_url = Routes::Dashboard(dashboard::Routes::Settings).to_url();
-        router().navigate_to_url(dashboard_url.clone());
+        router().navigate_to_url(dashboard_url.clone(), &model);
+
+        // The guard denies the anonymous user, so the router does not go to
+        // the dashboard.
+        assert_ne!(
+            router().current_route(),
+            Routes::Dashboard(dashboard::Routes::Settings)
+        );
+        assert_eq!(&model.dashboard.stuff, "");
 
+        model.user = Some(UserLogged {
+            name: "test_user".to_string(),
+        });
+        router().navigate_to_url(dashboard_url.clone(), &model);
         update(
             Msg::UrlChanged(subs::UrlChanged(dashboard_url)),
             &mut model,
@@ -307,7 +465,7 @@ mod test {
             .parse()
             .unwrap();
 
-        router().navigate_to_url(admin_url.clone());
+        router().navigate_to_url(admin_url.clone(), &model);
 
         update(
             Msg::UrlChanged(subs::UrlChanged(admin_url)),
@@ -318,6 +476,71 @@ mod test {
         assert_eq!(&model.admin.admin_id, "1");
         assert_eq!(&model.admin.admin_privilege, "high");
     }
+    #[wasm_bindgen_test]
+    fn test_router_navigation_and_guard_redirect() {
+        let mut model = Model {
+            dashboard: dashboard::Model::default(),
+            admin: admin::Model::default(),
+            other: other::Model::default(),
+            user: None,
+        };
+        let document = web_sys::window()
+            .expect("no global `window` exists")
+            .document()
+            .expect("should have a document on window");
+        let val = document
+            .create_element("section")
+            .expect("should add section");
+        val.set_id("app");
+
+        let app = App::start(val, init, update, view);
+        let mut orders = OrdersContainer::new(app);
+
+        // The guards of the route navigated to redirect the anonymous user,
+        // the router goes to the route redirected to instead.
+        router().navigate_to_url(Routes::Account.to_url(), &model);
+        assert_eq!(router().current_route(), Routes::Login);
+        assert!(!router().with_history(|history| history.contains(&Routes::Account)));
+        assert_eq!(Url::current(), Routes::Login.to_url());
+
+        // The url of a link is pushed by Seed, the router replaces the route
+        // when it is initialised.
+        Routes::Account.to_url().go_and_push();
+        router().confirm_navigation(Routes::Account.to_url());
+        let history_length = router().with_history(<[Routes]>::len);
+        update(
+            Msg::UrlChanged(subs::UrlChanged(Routes::Account.to_url())),
+            &mut model,
+            &mut orders,
+        );
+        assert_eq!(router().current_route(), Routes::Login);
+        assert_eq!(router().with_history(<[Routes]>::len), history_length);
+        assert_eq!(Url::current(), Routes::Login.to_url());
+
+        // The route redirected to is initialised, here the settings holding
+        // the reports of the dashboard.
+        model.user = Some(UserLogged {
+            name: "test_user".to_string(),
+        });
+        let reports_url = Dashboard(dashboard::Routes::Reports).to_url();
+        reports_url.go_and_push();
+        router().confirm_navigation(reports_url.clone());
+        update(
+            Msg::UrlChanged(subs::UrlChanged(reports_url)),
+            &mut model,
+            &mut orders,
+        );
+        assert_eq!(
+            router().current_route(),
+            Dashboard(dashboard::Routes::Settings)
+        );
+        assert_eq!(&model.dashboard.stuff, "init_has_been_done");
+        assert_eq!(
+            Url::current(),
+            Dashboard(dashboard::Routes::Settings).to_url()
+        );
+    }
+
     #[wasm_bindgen_test]
     fn test_router_navigation_and_page_init_with_id_and_rename() {
         let mut model = Model {
@@ -338,7 +561,7 @@ mod test {
         let mut orders = OrdersContainer::new(app);
 
         let stuff_url: Url = "http://localhost/my_stuff/123/files".parse().unwrap();
-        router().navigate_to_url(stuff_url.clone());
+        router().navigate_to_url(stuff_url.clone(), &model);
         update(
             Msg::UrlChanged(subs::UrlChanged(stuff_url)),
             &mut model,
@@ -105,4 +105,15 @@ mod test {
         router().init(url);
         assert_eq!(my_router.current_route(), Routes::Login);
     }
+
+    #[wasm_bindgen_test]
+    fn test_router_with_routing_mode_and_base_path() {
+        add_router!(RoutingMode::Hash, "/apps/crm/");
+        let url = router().url_for(&Routes::Login);
+        router().navigate_to_url::<_, ()>(url.clone(), &());
+
+        assert_eq!(url.path(), &["apps".to_string(), "crm".to_string()]);
+        assert_eq!(url.hash().unwrap(), "/login");
+        assert_eq!(router().current_route(), Routes::Login);
+    }
 }
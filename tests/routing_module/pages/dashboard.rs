@@ -12,6 +12,12 @@ pub enum Routes {
     NotFound,
     #[view = " => settings"]
     Settings,
+    #[guard(check = initialised, otherwise = not_initialised)]
+    #[view(render = settings)]
+    Private,
+    #[guard(check = settings_first)]
+    #[view(render = settings)]
+    Reports,
 }
 pub enum Msg {}
 pub fn init(_: Url, _: &mut Model, _: &Routes, _: &mut impl Orders<Msg>) -> Model {
@@ -38,3 +44,19 @@ fn not_found(_: &Model) -> Node<Msg> {
 pub fn settings(_: &Model) -> Node<Msg> {
     div!["settings"]
 }
+
+pub fn not_initialised(_: &Model) -> Node<Msg> {
+    div!["not_initialised"]
+}
+
+fn initialised(model: &Model) -> Option<bool> {
+    Some(!model.stuff.is_empty())
+}
+
+fn settings_first(model: &Model) -> GuardResult<Routes, Msg> {
+    if model.stuff.is_empty() {
+        GuardResult::Redirect(Routes::Settings)
+    } else {
+        GuardResult::Allow
+    }
+}
@@ -0,0 +1,11 @@
+use router_derive::*;
+
+#[derive(Debug, PartialEq, Clone, RoutingModules)]
+pub enum Routes {
+    #[guard(model = user, check = logged_in, model = user)]
+    Home,
+    #[default_route]
+    NotFound,
+}
+
+fn main() {}
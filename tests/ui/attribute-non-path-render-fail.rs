@@ -0,0 +1,11 @@
+use router_derive::*;
+
+#[derive(Debug, PartialEq, Clone, RoutingModules)]
+pub enum Routes {
+    #[view(render = "home")]
+    Home,
+    #[default_route]
+    NotFound,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use router_derive::*;
+
+#[derive(Debug, PartialEq, Clone, RoutingModules)]
+pub enum Routes {
+    #[guard(model = user, checks = logged_in)]
+    Home,
+    #[default_route]
+    NotFound,
+}
+
+fn main() {}
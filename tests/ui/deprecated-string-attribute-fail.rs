@@ -0,0 +1,38 @@
+#![deny(deprecated)]
+use router_derive::*;
+use seed::{prelude::*, *};
+use seed_routing::*;
+
+pub struct Model {
+    pub user: Option<bool>,
+}
+
+pub enum Msg {}
+
+#[derive(Debug, PartialEq, Clone, RoutingModules)]
+pub enum Routes {
+    // The string form can leave out the last keys, here `otherwise`.
+    #[guard = "user => logged_in"]
+    #[view = " => home"]
+    Home,
+    #[default_route]
+    #[view(render = not_found)]
+    NotFound,
+}
+
+fn logged_in(user: Option<&bool>) -> GuardResult<Routes, Msg> {
+    match user {
+        Some(true) => GuardResult::Allow,
+        _ => GuardResult::Pending,
+    }
+}
+
+fn home(_: &Model) -> Node<Msg> {
+    div!["home"]
+}
+
+fn not_found(_: &Model) -> Node<Msg> {
+    div!["not found"]
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use router_derive::*;
+
+#[derive(Debug, PartialEq, Clone, ParseUrl)]
+pub enum Routes {
+    #[as_path = ""]
+    User(String),
+    NotFound,
+}
+
+fn main() {}
@@ -0,0 +1,81 @@
+use router_derive::*;
+use seed::{prelude::*, *};
+use seed_routing::*;
+
+mod tasks {
+    use router_derive::*;
+    use seed::{prelude::*, *};
+    use seed_routing::*;
+
+    /// Routes deriving only `ParseUrl`, with no other attribute, are nested
+    /// in the routes of `RoutingModules`.
+    #[derive(Debug, PartialEq, Clone, ParseUrl)]
+    pub enum Routes {
+        Task { id: String },
+        Root,
+    }
+
+    #[derive(Default)]
+    pub struct Model;
+
+    pub enum Msg {}
+
+    pub fn init(_: Url, _: &mut Model, _: &Routes, _: &mut impl Orders<Msg>) -> Model {
+        Model
+    }
+
+    pub fn view(_: &Routes, _: &Model) -> Node<Msg> {
+        div!["tasks"]
+    }
+}
+
+pub struct Model {
+    pub tasks: tasks::Model,
+    pub logged: bool,
+}
+
+pub enum Msg {
+    Tasks(tasks::Msg),
+}
+
+#[derive(Debug, PartialEq, Clone, RoutingModules)]
+pub enum Routes {
+    #[guard(check = logged_in)]
+    Tasks(tasks::Routes),
+    #[view(render = login)]
+    Login,
+    #[default_route]
+    #[view(render = login)]
+    NotFound,
+}
+
+fn logged_in(model: &Model) -> GuardResult<Routes, Msg> {
+    if model.logged {
+        GuardResult::Allow
+    } else {
+        GuardResult::Redirect(Routes::Login)
+    }
+}
+
+fn login(_: &Model) -> Node<Msg> {
+    div!["login"]
+}
+
+fn main() {
+    let model = Model {
+        tasks: tasks::Model,
+        logged: false,
+    };
+    let (route, _) =
+        resolve_guards::<Routes, Model, Msg>(Routes::Tasks(tasks::Routes::Root), &model);
+    assert_eq!(route, Routes::Login);
+
+    let model = Model {
+        tasks: tasks::Model,
+        logged: true,
+    };
+    let task = Routes::Tasks(tasks::Routes::Task { id: "1".to_string() });
+    let (route, result) = resolve_guards::<Routes, Model, Msg>(task.clone(), &model);
+    assert_eq!(route, task);
+    assert!(matches!(result, GuardResult::Allow));
+}
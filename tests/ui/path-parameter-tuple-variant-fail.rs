@@ -0,0 +1,10 @@
+use router_derive::*;
+
+#[derive(Debug, PartialEq, Clone, ParseUrl)]
+pub enum Routes {
+    #[as_path = "users/:user_id"]
+    User(String),
+    NotFound,
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+use seed::prelude::*;
+use router_derive::*;
+use seed_routing::*;
+
+/// A route with an `id` does not match the same urls as the route without,
+/// so both can be at the same path.
+#[derive(Debug, PartialEq, Clone, ParseUrl)]
+pub enum Routes {
+    Profile,
+    #[as_path = "profile"]
+    ProfileById { id: String },
+}
+
+fn main() {
+    assert_eq!(Routes::parse_path("/profile").unwrap(), Routes::Profile);
+    assert_eq!(
+        Routes::parse_path("/profile/3").unwrap(),
+        Routes::ProfileById { id: "3".to_string() }
+    );
+}
@@ -0,0 +1,10 @@
+use router_derive::*;
+
+#[derive(Debug, PartialEq, Clone, ParseUrl)]
+pub enum Routes {
+    Profile { id: String },
+    #[as_path = "profile/:user_id"]
+    User { user_id: String },
+}
+
+fn main() {}
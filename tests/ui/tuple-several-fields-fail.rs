@@ -0,0 +1,8 @@
+use router_derive::*;
+
+#[derive(Debug, PartialEq, Clone, ParseUrl)]
+pub enum Routes {
+    Pair(String, String),
+}
+
+fn main() {}
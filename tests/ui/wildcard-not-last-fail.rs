@@ -0,0 +1,9 @@
+use router_derive::*;
+
+#[derive(Debug, PartialEq, Clone, ParseUrl)]
+pub enum Routes {
+    #[as_path = "files/*/raw"]
+    Files { rest: String },
+}
+
+fn main() {}
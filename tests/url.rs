@@ -1,4 +1,5 @@
 mod router;
+#[allow(deprecated)]
 mod routing_module;
 #[cfg(test)]
 mod test {
@@ -8,6 +9,8 @@ mod test {
     use router_derive::*;
     use seed::prelude::{IndexMap, *};
     use seed_routing::*;
+    use serde::{Deserialize, Serialize};
+    use std::net::Ipv4Addr;
 
     use wasm_bindgen_test::*;
     wasm_bindgen_test_configure!(run_in_browser);
@@ -61,6 +64,181 @@ mod test {
         Google,
         Microsoft,
     }
+    #[derive(Debug, PartialEq, Clone, ParseUrl, WithDefaultRoute)]
+    pub enum TypedRoutes {
+        Invoice {
+            id: u32,
+        },
+        Task {
+            id: u32,
+            children: Apis,
+        },
+        Host(Ipv4Addr),
+        #[default_route]
+        NotFound,
+    }
+
+    #[derive(Debug, PartialEq, Clone, ParseUrl, WithDefaultRoute)]
+    pub enum PatternRoutes {
+        #[as_path = "orgs/:org_id/projects/:project_id"]
+        Project {
+            org_id: String,
+            project_id: u32,
+            query: IndexMap<String, String>,
+        },
+        #[as_path = "orgs/:org_id/members/:member_id"]
+        Member {
+            org_id: String,
+            member_id: String,
+            id: u32,
+            children: Apis,
+        },
+        #[as_path = "api/v1"]
+        Api(Apis),
+        #[default_route]
+        NotFound,
+    }
+
+    #[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+    #[serde(default)]
+    pub struct InvoiceFilters {
+        pub page: u32,
+        pub paid: Option<bool>,
+        pub customer: Option<String>,
+    }
+
+    #[derive(Debug, PartialEq, Clone, ParseUrl, WithDefaultRoute)]
+    pub enum FilterRoutes {
+        Invoices {
+            query: InvoiceFilters,
+        },
+        Customer {
+            id: u32,
+            query: InvoiceFilters,
+            children: Apis,
+        },
+        #[default_route]
+        NotFound,
+    }
+
+    #[derive(Debug, PartialEq, Clone, ParseUrl, WithDefaultRoute)]
+    pub enum SectionRoutes {
+        Dashboard(SectionDashboardRoutes),
+        Admin {
+            id: u32,
+            children: SectionDashboardRoutes,
+        },
+        #[default_route]
+        NotFound,
+    }
+
+    #[derive(Debug, PartialEq, Clone, ParseUrl, WithDefaultRoute)]
+    pub enum SectionDashboardRoutes {
+        Stats,
+        #[default_route]
+        NotFound,
+        #[as_path = ""]
+        Root,
+    }
+
+    #[derive(Debug, PartialEq, Clone, ParseUrl, WithDefaultRoute)]
+    pub enum MatchingRoutes {
+        Admin,
+        Home,
+        #[prefix_match]
+        Legacy,
+        Profile {
+            id: String,
+        },
+        #[prefix_match]
+        Repo {
+            id: String,
+        },
+        Dashboard(SectionDashboardRoutes),
+        #[default_route]
+        NotFound,
+    }
+
+    #[derive(Debug, PartialEq, Clone, ParseUrl, WithDefaultRoute)]
+    pub enum SpecificRoutes {
+        #[default_route]
+        #[as_path = ""]
+        Home,
+        #[prefix_match]
+        #[as_path = "dash"]
+        Dash,
+        Dashboard(SectionDashboardRoutes),
+        Orgs {
+            id: String,
+        },
+        #[as_path = "orgs/new"]
+        NewOrg,
+        #[as_path = "dashboard/settings"]
+        Settings,
+    }
+
+    #[derive(Debug, PartialEq, Clone, ParseUrl, WithDefaultRoute)]
+    pub enum WildcardRoutes {
+        #[as_path = "files/*"]
+        Files { rest: Vec<String> },
+        #[as_path = "docs/:version/*"]
+        Docs {
+            version: String,
+            rest: String,
+            query: IndexMap<String, String>,
+        },
+        #[as_path = "files/shared"]
+        Shared,
+        #[default_route]
+        NotFound,
+    }
+
+    #[derive(Debug, PartialEq, Clone, ParseUrl, WithDefaultRoute)]
+    pub enum LostRoutes {
+        Admin,
+        Docs(LostDocsRoutes),
+        #[default_route]
+        NotFound {
+            path: String,
+        },
+    }
+
+    #[derive(Debug, PartialEq, Clone, ParseUrl, WithDefaultRoute)]
+    pub enum LostDocsRoutes {
+        Intro,
+        #[default_route]
+        Missing {
+            path: String,
+        },
+    }
+
+    #[derive(Debug, PartialEq, Clone, ParseUrl, WithDefaultRoute)]
+    pub enum LostUrlRoutes {
+        Home,
+        #[default_route]
+        Missing(Url),
+    }
+
+    #[derive(Debug, PartialEq, Clone, ParseUrl, WithDefaultRoute)]
+    pub enum AnchorRoutes {
+        Docs {
+            id: String,
+            query: IndexMap<String, String>,
+            fragment: Option<String>,
+        },
+        Settings {
+            children: AnchorSettingsRoutes,
+        },
+        Home,
+        #[default_route]
+        NotFound,
+    }
+
+    #[derive(Debug, PartialEq, Clone, ParseUrl)]
+    pub enum AnchorSettingsRoutes {
+        Profile { fragment: Option<String> },
+    }
+
     #[derive(Debug, PartialEq, Clone, ParseUrl)]
     pub enum Routes {
         Stuff,
@@ -79,7 +257,8 @@ mod test {
 
     #[wasm_bindgen_test]
     fn test_url_for_tatrix() {
-        let url: Url = "http://localhost/olives/flowering/2019/units/Camor/farms/Cacalsoh?from-year=2018&kpi=Average"
+        // The id is a single segment, so its `/` are encoded.
+        let url: Url = "http://localhost/olives/flowering/2019%2Funits%2FCamor%2Ffarms%2FCacalsoh?from-year=2018&kpi=Average"
             .parse()
             .unwrap();
 
@@ -95,7 +274,7 @@ mod test {
         .to_url();
         assert_eq!(url, url_to_compare);
 
-        let url: Url = "http://localhost/olives/flowering/2019/units/Camor"
+        let url: Url = "http://localhost/olives/flowering/2019%2Funits%2FCamor"
             .parse()
             .unwrap();
 
@@ -226,7 +405,7 @@ mod test {
             }
         );
 
-        let string = "/profile/1/repos";
+        let string = "/profile/1";
 
         let route = ExampleRoutes::parse_path(string).unwrap();
         assert_eq!(
@@ -236,6 +415,10 @@ mod test {
             }
         );
 
+        // Segments left after the id do not match, unless `#[prefix_match]`.
+        let string = "/profile/1/repos";
+        assert!(ExampleRoutes::parse_path(string).is_err());
+
         let mut query: IndexMap<String, String> = IndexMap::new();
 
         query.insert("user".to_string(), "arn".to_string());
@@ -416,6 +599,619 @@ mod test {
         assert_eq!(url_to_compare, route.to_url());
     }
 
+    #[wasm_bindgen_test]
+    fn test_typed_id_parameter() {
+        assert_eq!(
+            TypedRoutes::parse_path("/invoice/42").unwrap(),
+            TypedRoutes::Invoice { id: 42 }
+        );
+        assert_eq!(
+            TypedRoutes::parse_path("/task/7/google").unwrap(),
+            TypedRoutes::Task {
+                id: 7,
+                children: Apis::Google
+            }
+        );
+
+        let url_to_compare: Url = "/invoice/42".parse().unwrap();
+        assert_eq!(TypedRoutes::Invoice { id: 42 }.to_url(), url_to_compare);
+        assert_eq!(
+            TypedRoutes::from_url(url_to_compare).unwrap(),
+            TypedRoutes::Invoice { id: 42 }
+        );
+
+        assert!(TypedRoutes::parse_path("/invoice/forty_two").is_err());
+        assert!(TypedRoutes::parse_path("/task/-1/google").is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_foreign_tuple_payload() {
+        let route = TypedRoutes::Host(Ipv4Addr::new(127, 0, 0, 1));
+        assert_eq!(TypedRoutes::parse_path("/host/127.0.0.1").unwrap(), route);
+        assert_eq!(route.to_url(), Url::new().set_path(["host", "127.0.0.1"]));
+        assert!(route.restores_scroll());
+        assert_eq!(route.fragment(), None);
+        assert!(TypedRoutes::parse_path("/host/localhost").is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_path_with_parameters() {
+        let mut query: IndexMap<String, String> = IndexMap::new();
+        query.insert("tab".to_string(), "issues".to_string());
+        let project = PatternRoutes::Project {
+            org_id: "seed-rs".to_string(),
+            project_id: 42,
+            query,
+        };
+        assert_eq!(
+            PatternRoutes::parse_path("/orgs/seed-rs/projects/42?tab=issues").unwrap(),
+            project
+        );
+        assert_eq!(project.as_path(), "/orgs/seed-rs/projects/42?tab=issues");
+
+        let member = PatternRoutes::Member {
+            org_id: "seed-rs".to_string(),
+            member_id: "arn".to_string(),
+            id: 7,
+            children: Apis::Google,
+        };
+        assert_eq!(
+            PatternRoutes::parse_path("/orgs/seed-rs/members/arn/7/google").unwrap(),
+            member
+        );
+        assert_eq!(member.as_path(), "/orgs/seed-rs/members/arn/7/google");
+
+        assert_eq!(
+            PatternRoutes::parse_path("/api/v1/facebook").unwrap(),
+            PatternRoutes::Api(Apis::Facebook)
+        );
+
+        assert!(PatternRoutes::parse_path("/orgs/seed-rs/projects/latest").is_err());
+        assert!(PatternRoutes::parse_path("/orgs/seed-rs").is_err());
+        assert!(PatternRoutes::parse_path("/orgs//projects/42").is_err());
+
+        let url_to_compare: Url = "/orgs/seed-rs/members/arn/7/google".parse().unwrap();
+        assert_eq!(
+            PatternRoutes::from_url(url_to_compare).unwrap(),
+            PatternRoutes::Member {
+                org_id: "seed-rs".to_string(),
+                member_id: "arn".to_string(),
+                id: 7,
+                children: Apis::Google,
+            }
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_typed_query() {
+        let invoices = FilterRoutes::Invoices {
+            query: InvoiceFilters {
+                page: 2,
+                paid: Some(false),
+                customer: Some("seed rs".to_string()),
+            },
+        };
+        assert_eq!(
+            FilterRoutes::parse_path("/invoices?page=2&paid=false&customer=seed+rs").unwrap(),
+            invoices
+        );
+        assert_eq!(
+            invoices.as_path(),
+            "/invoices?page=2&paid=false&customer=seed+rs"
+        );
+
+        assert_eq!(
+            FilterRoutes::parse_path("/invoices").unwrap(),
+            FilterRoutes::Invoices {
+                query: InvoiceFilters::default()
+            }
+        );
+        assert_eq!(
+            FilterRoutes::parse_path("/customer/7/google?page=3").unwrap(),
+            FilterRoutes::Customer {
+                id: 7,
+                query: InvoiceFilters {
+                    page: 3,
+                    ..InvoiceFilters::default()
+                },
+                children: Apis::Google,
+            }
+        );
+        assert!(FilterRoutes::parse_path("/invoices?page=first").is_err());
+
+        let url_to_compare: Url = "/invoices?page=2&paid=false&customer=seed+rs"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            FilterRoutes::Invoices {
+                query: InvoiceFilters {
+                    page: 2,
+                    paid: Some(false),
+                    customer: Some("seed rs".to_string()),
+                },
+            }
+            .to_url(),
+            url_to_compare
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_encoded_round_trip() {
+        let values = [
+            "2019/units/Camor",
+            "a&b=c?d#e",
+            "hello world",
+            "100% + 1",
+            "Günter",
+            "日本語",
+        ];
+        for value in values.iter() {
+            let mut query: IndexMap<String, String> = IndexMap::new();
+            query.insert(value.to_string(), value.to_string());
+            let route = PatternRoutes::Project {
+                org_id: value.to_string(),
+                project_id: 1,
+                query,
+            };
+            let path = route.clone().as_path();
+            assert!(path.starts_with("/orgs/"));
+            assert_eq!(path.matches('/').count(), 4);
+            assert_eq!(path.matches('?').count(), 1);
+            assert_eq!(PatternRoutes::parse_path(&path).unwrap(), route);
+
+            let route = ExampleRoutes::Profile {
+                id: value.to_string(),
+            };
+            assert_eq!(
+                ExampleRoutes::parse_path(&route.clone().as_path()).unwrap(),
+                route
+            );
+
+            let route = InvoiceFilters {
+                customer: Some(value.to_string()),
+                ..InvoiceFilters::default()
+            };
+            let route = FilterRoutes::Invoices { query: route };
+            assert_eq!(
+                FilterRoutes::parse_path(&route.clone().as_path()).unwrap(),
+                route
+            );
+        }
+
+        let route = ExampleRoutes::Profile {
+            id: "Günter/Grass".to_string(),
+        };
+        assert_eq!(route.to_url().path(), ["profile", "Günter/Grass"]);
+        assert_eq!(ExampleRoutes::from_url(route.to_url()).unwrap(), route);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_bad_urls_return_errors() {
+        assert!(matches!(
+            ExampleRoutes::parse_path("/profile"),
+            Err(ParseError::By(_, _))
+        ));
+        assert!(ExampleRoutes::parse_path("/other").is_err());
+        assert!(ExampleRoutes::parse_path("/other/1/garbage").is_err());
+        assert!(ExampleRoutes::parse_path("/other/1/projects/2/garbage?user=arn").is_err());
+        assert!(ExampleRoutes::parse_path("/dashboard/my_stuff").is_err());
+        assert!(TypedRoutes::parse_path("/task/7").is_err());
+
+        assert_eq!(
+            ExampleRoutes::parse_path("/other/1/garbage")
+                .unwrap_err()
+                .to_string(),
+            "ExampleRoutes: Settings: invalid segment 'garbage': does not match any route"
+        );
+        assert_eq!(
+            ExampleRoutes::parse_path("/other/1/garbage#top")
+                .unwrap_err()
+                .to_string(),
+            "ExampleRoutes: Settings: invalid segment 'garbage': does not match any route"
+        );
+        assert_eq!(
+            TypedRoutes::parse_path("/invoice/forty_two")
+                .unwrap_err()
+                .to_string(),
+            "TypedRoutes: invalid segment 'forty_two': cannot be parsed into u32"
+        );
+
+        let mut query: IndexMap<String, String> = IndexMap::new();
+        query.insert("flag".to_string(), "".to_string());
+        assert_eq!(
+            ExampleRoutes::parse_path("/admin?flag").unwrap(),
+            ExampleRoutes::Admin { query }
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_children_default_route() {
+        assert_eq!(
+            SectionRoutes::parse_path("/dashboard/unknown").unwrap(),
+            SectionRoutes::Dashboard(SectionDashboardRoutes::NotFound)
+        );
+        assert_eq!(
+            SectionRoutes::parse_path("/dashboard/stats").unwrap(),
+            SectionRoutes::Dashboard(SectionDashboardRoutes::Stats)
+        );
+        assert_eq!(
+            SectionRoutes::parse_path("/dashboard").unwrap(),
+            SectionRoutes::Dashboard(SectionDashboardRoutes::Root)
+        );
+        assert_eq!(
+            SectionRoutes::parse_path("/admin/3/unknown/path").unwrap(),
+            SectionRoutes::Admin {
+                id: 3,
+                children: SectionDashboardRoutes::NotFound
+            }
+        );
+
+        // The top level route has no parent, the router handles its default route.
+        assert!(SectionRoutes::parse_path("/unknown").is_err());
+        assert!(SectionDashboardRoutes::parse_path("/unknown").is_err());
+        // The id of the parent still has to be valid.
+        assert!(SectionRoutes::parse_path("/admin/three/stats").is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_exact_matching() {
+        assert_eq!(
+            MatchingRoutes::parse_path("/admin").unwrap(),
+            MatchingRoutes::Admin
+        );
+        assert_eq!(
+            MatchingRoutes::parse_path("/admin/").unwrap(),
+            MatchingRoutes::Admin
+        );
+        assert_eq!(
+            MatchingRoutes::parse_path("/admin?user=arn").unwrap(),
+            MatchingRoutes::Admin
+        );
+        assert!(MatchingRoutes::parse_path("/administrator").is_err());
+        assert!(matches!(
+            MatchingRoutes::parse_path("/home/anything/else"),
+            Err(ParseError::By(_, err)) if matches!(*err, ParseError::RemainingSegments)
+        ));
+        assert!(MatchingRoutes::parse_path("/profile/1/repos").is_err());
+        assert!(MatchingRoutes::parse_path("/dashboardstats").is_err());
+        assert_eq!(
+            MatchingRoutes::parse_path("/dashboard/stats").unwrap(),
+            MatchingRoutes::Dashboard(SectionDashboardRoutes::Stats)
+        );
+
+        // The old behaviour is kept with `#[prefix_match]`.
+        assert_eq!(
+            MatchingRoutes::parse_path("/legacy/anything/else").unwrap(),
+            MatchingRoutes::Legacy
+        );
+        assert_eq!(
+            MatchingRoutes::parse_path("/legacyish").unwrap(),
+            MatchingRoutes::Legacy
+        );
+        assert_eq!(
+            MatchingRoutes::parse_path("/repo/1/issues").unwrap(),
+            MatchingRoutes::Repo {
+                id: "1".to_string()
+            }
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_most_specific_route() {
+        assert_eq!(
+            SpecificRoutes::parse_path("/orgs/new").unwrap(),
+            SpecificRoutes::NewOrg
+        );
+        assert_eq!(
+            SpecificRoutes::parse_path("/orgs/seed").unwrap(),
+            SpecificRoutes::Orgs {
+                id: "seed".to_string()
+            }
+        );
+        assert_eq!(
+            SpecificRoutes::parse_path("/dashboard/settings").unwrap(),
+            SpecificRoutes::Settings
+        );
+        assert_eq!(
+            SpecificRoutes::parse_path("/dashboard/stats").unwrap(),
+            SpecificRoutes::Dashboard(SectionDashboardRoutes::Stats)
+        );
+        assert_eq!(
+            SpecificRoutes::parse_path("/dashboard").unwrap(),
+            SpecificRoutes::Dashboard(SectionDashboardRoutes::Root)
+        );
+        assert_eq!(
+            SpecificRoutes::parse_path("/dashes").unwrap(),
+            SpecificRoutes::Dash
+        );
+        assert_eq!(
+            SpecificRoutes::parse_path("/").unwrap(),
+            SpecificRoutes::Home
+        );
+        assert!(SpecificRoutes::parse_path("/unknown").is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_wildcard() {
+        assert_eq!(
+            WildcardRoutes::parse_path("/files/photos/2020/beach%20day.png").unwrap(),
+            WildcardRoutes::Files {
+                rest: vec![
+                    "photos".to_string(),
+                    "2020".to_string(),
+                    "beach day.png".to_string()
+                ]
+            }
+        );
+        assert_eq!(
+            WildcardRoutes::parse_path("/files").unwrap(),
+            WildcardRoutes::Files { rest: Vec::new() }
+        );
+        assert_eq!(
+            WildcardRoutes::parse_path("/files/shared").unwrap(),
+            WildcardRoutes::Shared
+        );
+        let mut query: IndexMap<String, String> = IndexMap::new();
+        query.insert("lang".to_string(), "en".to_string());
+        assert_eq!(
+            WildcardRoutes::parse_path("/docs/v2/guide/routing?lang=en").unwrap(),
+            WildcardRoutes::Docs {
+                version: "v2".to_string(),
+                rest: "guide/routing".to_string(),
+                query,
+            }
+        );
+        assert!(WildcardRoutes::parse_path("/filesystem").is_err());
+
+        let routes = [
+            WildcardRoutes::Files {
+                rest: vec!["a b".to_string(), "c%d".to_string()],
+            },
+            WildcardRoutes::Files { rest: Vec::new() },
+            WildcardRoutes::Docs {
+                version: "v1".to_string(),
+                rest: "intro/getting started".to_string(),
+                query: IndexMap::new(),
+            },
+        ];
+        for route in routes.iter() {
+            let path = route.clone().as_path();
+            assert_eq!(&WildcardRoutes::parse_path(&path).unwrap(), route);
+        }
+        assert_eq!(
+            WildcardRoutes::Files { rest: Vec::new() }.as_path(),
+            "/files"
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_wildcard_to_url() {
+        let route = WildcardRoutes::Files {
+            rest: vec!["photos".to_string(), "beach day.png".to_string()],
+        };
+        let url: Url = "/files/photos/beach%20day.png".parse().unwrap();
+        assert_eq!(route.to_url(), url);
+        assert_eq!(WildcardRoutes::from_url(url).unwrap(), route);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_default_route_with_url() {
+        assert_eq!(
+            LostRoutes::default(),
+            LostRoutes::NotFound {
+                path: String::new()
+            }
+        );
+        assert!(LostRoutes::NotFound {
+            path: "/anything".to_string()
+        }
+        .is_default());
+        assert!(!LostRoutes::Admin.is_default());
+        assert_eq!(LostUrlRoutes::default(), LostUrlRoutes::Missing(Url::new()));
+        assert!(LostUrlRoutes::default().is_default());
+
+        // The default route is filled by the router, not parsed from a path.
+        assert!(LostRoutes::parse_path("/not_found").is_err());
+        assert!(LostRoutes::parse_path("/admin/settings").is_err());
+        let route = LostRoutes::NotFound {
+            path: "/some/where?page=2".to_string(),
+        };
+        assert_eq!(route.as_path(), "/some/where?page=2");
+
+        // A nested default route keeps the part of the url it did not match.
+        assert_eq!(
+            LostRoutes::parse_path("/docs/intro").unwrap(),
+            LostRoutes::Docs(LostDocsRoutes::Intro)
+        );
+        assert_eq!(
+            LostRoutes::parse_path("/docs/unknown/page").unwrap(),
+            LostRoutes::Docs(LostDocsRoutes::Missing {
+                path: "/unknown/page".to_string()
+            })
+        );
+        assert_eq!(
+            LostRoutes::Docs(LostDocsRoutes::Missing {
+                path: "/unknown/page".to_string()
+            })
+            .as_path(),
+            "/docs/unknown/page"
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_default_route_for_url() {
+        let url: Url = "/some/where?page=2".parse().unwrap();
+        assert_eq!(
+            LostRoutes::default_route_for(&url),
+            Some(LostRoutes::NotFound {
+                path: "/some/where?page=2".to_string()
+            })
+        );
+        assert_eq!(
+            LostUrlRoutes::default_route_for(&url),
+            Some(LostUrlRoutes::Missing(url.clone()))
+        );
+        assert_eq!(ExampleRoutes::default_route_for(&url), None);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_fragment() {
+        let mut query: IndexMap<String, String> = IndexMap::new();
+        query.insert("lang".to_string(), "en".to_string());
+        let route = AnchorRoutes::Docs {
+            id: "routing".to_string(),
+            query,
+            fragment: Some("nested routes".to_string()),
+        };
+        assert_eq!(
+            AnchorRoutes::parse_path("/docs/routing?lang=en#nested%20routes").unwrap(),
+            route
+        );
+        assert_eq!(
+            route.clone().as_path(),
+            "/docs/routing?lang=en#nested%20routes"
+        );
+        assert_eq!(
+            AnchorRoutes::parse_path("/docs/routing").unwrap(),
+            AnchorRoutes::Docs {
+                id: "routing".to_string(),
+                query: IndexMap::new(),
+                fragment: None,
+            }
+        );
+
+        let route = AnchorRoutes::Settings {
+            children: AnchorSettingsRoutes::Profile {
+                fragment: Some("avatar".to_string()),
+            },
+        };
+        assert_eq!(
+            AnchorRoutes::parse_path("/settings/profile#avatar").unwrap(),
+            route
+        );
+        assert_eq!(route.as_path(), "/settings/profile#avatar");
+
+        // Routes without a fragment field still match urls with a fragment.
+        assert_eq!(
+            AnchorRoutes::parse_path("/home#top").unwrap(),
+            AnchorRoutes::Home
+        );
+        // The fragment is stripped before matching the root route.
+        assert_eq!(
+            ExampleRoutes::parse_path("/#section").unwrap(),
+            ExampleRoutes::Root
+        );
+        assert_eq!(
+            ExampleRoutes::parse_path("#section").unwrap(),
+            ExampleRoutes::Root
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_fragment_url() {
+        let url: Url = "/docs/routing#install".parse().unwrap();
+        let route = AnchorRoutes::Docs {
+            id: "routing".to_string(),
+            query: IndexMap::new(),
+            fragment: Some("install".to_string()),
+        };
+        assert_eq!(AnchorRoutes::from_url(url.clone()).unwrap(), route);
+        assert_eq!(route.to_url(), url);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_hash_routing_mode() {
+        let router: Router<ExampleRoutes> =
+            Router::with_routing_mode(RoutingMode::Hash).with_history_backend(MemoryHistory::new());
+        router.navigate_to_url::<_, ()>(Url::new().set_hash("/profile/1?user=arn"), &());
+        let route = router.current_route();
+        let url_from_route = router.url_for(&ExampleRoutes::Profile {
+            id: "1".to_string(),
+        });
+        let nested_url = router.url_for(&ExampleRoutes::Other {
+            id: "2".to_string(),
+            children: Settings::Api(Apis::Facebook),
+        });
+        router.navigate_to_url::<_, ()>(Url::new().set_hash("/other/2/api/facebook"), &());
+        let nested_route = router.current_route();
+
+        assert_eq!(
+            route,
+            ExampleRoutes::Profile {
+                id: "1".to_string()
+            }
+        );
+        assert_eq!(url_from_route.hash().unwrap(), "/profile/1");
+        assert!(url_from_route.path().is_empty());
+        assert_eq!(nested_url.hash().unwrap(), "/other/2/api/facebook");
+        assert_eq!(
+            nested_route,
+            ExampleRoutes::Other {
+                id: "2".to_string(),
+                children: Settings::Api(Apis::Facebook),
+            }
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_base_path() {
+        let router: Router<ExampleRoutes> = Router::new()
+            .with_base_path("apps/crm")
+            .with_history_backend(MemoryHistory::new());
+        let route_for = |path: &str| {
+            router.navigate_to_url::<_, ()>(Url::new().set_path(path.split('/')), &());
+            router.current_route()
+        };
+        let nested = route_for("apps/crm/other/2/projects/14/facebook");
+        let root = route_for("apps/crm");
+        let outside = route_for("other/2/api/google");
+        let prefix = route_for("apps/crmx/admin");
+        let hash_url = Router::with_routing_mode(RoutingMode::Hash)
+            .with_base_path("apps/crm")
+            .url_for(&ExampleRoutes::Other {
+                id: "2".to_string(),
+                children: Settings::Api(Apis::Google),
+            });
+
+        assert_eq!(
+            nested,
+            ExampleRoutes::Other {
+                id: "2".to_string(),
+                children: Settings::Projects {
+                    id: "14".to_string(),
+                    query: IndexMap::new(),
+                    children: Apis::Facebook
+                },
+            }
+        );
+        assert_eq!(root, ExampleRoutes::Root);
+        assert_eq!(outside, ExampleRoutes::NotFound);
+        assert_eq!(prefix, ExampleRoutes::NotFound);
+        assert_eq!(hash_url.path(), &["apps".to_string(), "crm".to_string()]);
+        assert_eq!(hash_url.hash().unwrap(), "/other/2/api/google");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_base_path_to_url() {
+        let router: Router<ExampleRoutes> = Router::new().with_base_path("apps/crm");
+        let url = router.url_for(&ExampleRoutes::Other {
+            id: "2".to_string(),
+            children: Settings::Api(Apis::Google),
+        });
+        let root_url = router.url_for(&ExampleRoutes::Root);
+
+        assert_eq!(
+            url,
+            Url::new().set_path("apps/crm/other/2/api/google".split('/'))
+        );
+        assert_eq!(root_url, Url::new().set_path(["apps", "crm"]));
+        // The routes do not know about the base path.
+        assert_eq!(
+            ExampleRoutes::Root.to_url(),
+            Url::new().set_path(Vec::<String>::new())
+        );
+    }
+
     #[wasm_bindgen_test]
     fn test_default_route() {
         assert_eq!(ExampleRoutes::default(), ExampleRoutes::NotFound);
@@ -14,3 +14,85 @@ fn pass() {
 
 #[wasm_bindgen_test]
 pub fn test_created_url() {}
+
+mod history {
+    extern crate router_derive;
+    extern crate seed_routing;
+    use router_derive::*;
+    use seed::{
+        prelude::{js_sys, web_sys, JsValue},
+        Url,
+    };
+    use seed_routing::*;
+    use wasm_bindgen_futures::JsFuture;
+    use wasm_bindgen_test::*;
+
+    #[derive(Debug, PartialEq, Clone, ParseUrl, WithDefaultRoute)]
+    enum Routes {
+        Login,
+        Register,
+        Stuff,
+        #[default_route]
+        NotFound,
+    }
+
+    /// Move in the browser history as the buttons of the browser do, and wait
+    /// for the browser to get there.
+    async fn go(delta: i32) {
+        let window = web_sys::window().unwrap();
+        let moved = js_sys::Promise::new(&mut |resolve, _| window.set_onpopstate(Some(&resolve)));
+        window.history().unwrap().go_with_delta(delta).unwrap();
+        JsFuture::from(moved).await.unwrap();
+        window.set_onpopstate(None);
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_browser_buttons_and_reload() {
+        let browser = BrowserHistory;
+        let router: Router<Routes> = Router::new().with_history_storage(SessionStorage);
+        browser.push(&Routes::Login.to_url());
+        router.init(Url::current());
+        // Seed pushes the url of a link before notifying the router.
+        for route in &[Routes::Register, Routes::Stuff] {
+            browser.push(&route.to_url());
+            router.confirm_navigation(Url::current());
+        }
+
+        go(-1).await;
+        router.confirm_navigation(Url::current());
+        assert_eq!(router.current_route(), Routes::Register);
+        assert_eq!(router.current_history_index(), 1);
+        assert_eq!(browser.history_index(), Some(1));
+
+        go(1).await;
+        router.confirm_navigation(Url::current());
+        assert_eq!(router.current_route(), Routes::Stuff);
+        assert_eq!(router.current_history_index(), 2);
+
+        // A reload starts a new router on the entry the browser is at.
+        go(-2).await;
+        let reloaded: Router<Routes> = Router::new().with_history_storage(SessionStorage);
+        reloaded.init(Url::current());
+        assert_eq!(reloaded.current_route(), Routes::Login);
+        assert_eq!(reloaded.current_history_index(), 0);
+        assert_eq!(reloaded.peek_forward(), Some(Routes::Register));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_history_index_keeps_the_state() {
+        let history = web_sys::window().unwrap().history().unwrap();
+        let state = js_sys::Object::new();
+        js_sys::Reflect::set(&state, &"saved".into(), &JsValue::from_f64(120.)).unwrap();
+        history.replace_state(&state, "").unwrap();
+
+        BrowserHistory.set_history_index(4);
+        let state = history.state().unwrap();
+        assert_eq!(
+            js_sys::Reflect::get(&state, &"saved".into())
+                .unwrap()
+                .as_f64(),
+            Some(120.)
+        );
+        assert_eq!(BrowserHistory.history_index(), Some(4));
+    }
+}